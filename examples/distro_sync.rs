@@ -0,0 +1,41 @@
+/// Example of how to sync the whole system to the versions in the enabled repositories
+use dnf5daemon::transaction::Transaction;
+use dnf5daemon::{DnfDaemon, Error, Result};
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    match DnfDaemon::default().await {
+        Ok(dnf_daemon) => {
+            match dnf_daemon.read_all_repos().await {
+                Ok(rc) => println!("Read all repos returned: {:?}", rc),
+                Err(e) => eprintln!("Warning: read_all_repos failed: {:?}", e),
+            }
+
+            // an empty list means all installed packages
+            let pkgs: Vec<String> = vec![];
+            println!("->> Syncing all installed packages");
+            let mut transaction = Transaction::new(&dnf_daemon);
+            transaction.distro_sync(pkgs).await?;
+            match transaction.resolve().await {
+                Ok(transaction) => {
+                    // only show the result, run the transaction with `transaction.execute()`
+                    transaction.show();
+                }
+                Err(e) => match e {
+                    Error::TransactionNotResolved(report) => {
+                        eprintln!("Transaction errors:");
+                        eprintln!("{}", report);
+                    }
+                    _ => {
+                        return Err(e);
+                    }
+                },
+            }
+        }
+        Err(e) => {
+            eprintln!("Could not connect to dnf5daemon-server: {e}");
+        }
+    }
+
+    Ok(())
+}
@@ -13,7 +13,7 @@ async fn main() -> Result<()> {
     match DnfDaemon::default().await {
         Ok(dnf_daemon) => {
             // Ensure repositories are loaded (matches other examples' pattern)
-            match dnf_daemon.base.read_all_repos().await {
+            match dnf_daemon.read_all_repos().await {
                 Ok(rc) => println!("Read all repos returned: {:?}", rc),
                 Err(e) => eprintln!("Warning: read_all_repos failed: {:?}", e),
             }
@@ -37,8 +37,8 @@ async fn main() -> Result<()> {
                 }
             }
         }
-        Err(_) => {
-            eprintln!("Could not connect to dnf5daemon-server");
+        Err(e) => {
+            eprintln!("Could not connect to dnf5daemon-server: {e}");
         }
     }
 
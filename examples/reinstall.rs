@@ -6,7 +6,7 @@ use dnf5daemon::{DnfDaemon, Error, Result};
 async fn main() -> Result<()> {
     match DnfDaemon::default().await {
         Ok(dnf_daemon) => {
-            match dnf_daemon.base.read_all_repos().await {
+            match dnf_daemon.read_all_repos().await {
                 Ok(rc) => println!("Read all repos returned: {:?}", rc),
                 Err(e) => eprintln!("Warning: read_all_repos failed: {:?}", e),
             }
@@ -14,16 +14,19 @@ async fn main() -> Result<()> {
             let pkgs: Vec<String> = vec![String::from("0xFFFF")];
             println!("->> Reinstalling packages {:?}", pkgs);
             let mut transaction = Transaction::new(&dnf_daemon);
-            transaction.reinstall(pkgs).await.ok();
+            transaction.reinstall(pkgs).await?;
             match transaction.resolve().await {
-                Ok(_) => {
+                Ok(transaction) => {
                     transaction.show();
                     transaction.execute().await?;
                 }
                 Err(e) => match e {
-                    Error::TransactionNotResolved(msgs) => {
+                    Error::TransactionNotResolved(report) => {
                         eprintln!("Transaction errors:");
-                        eprintln!("{}", msgs);
+                        eprintln!("{}", report);
+                        for (kind, spec) in report.failed_specs() {
+                            eprintln!("  failed to {kind}: {spec}");
+                        }
                     }
                     _ => {
                         return Err(e);
@@ -31,8 +34,8 @@ async fn main() -> Result<()> {
                 },
             }
         }
-        Err(_) => {
-            eprintln!("Could not connect to dnf5daemon-server");
+        Err(e) => {
+            eprintln!("Could not connect to dnf5daemon-server: {e}");
         }
     }
 
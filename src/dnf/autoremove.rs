@@ -0,0 +1,393 @@
+use crate::DnfDaemon;
+use crate::Result;
+use crate::dnf::package::{from_variant, optional_variant};
+use crate::dnf::proxy::RPM;
+use crate::errors::DbusContext;
+use crate::package::{ListOptions, PackageAttr, PackageReason, PackageRecord, Scope};
+use crate::transaction::Transaction;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use zbus::zvariant::OwnedValue;
+
+// region:    --- InstalledPackage
+
+/// An installed package with the reason it was installed and its dependencies
+#[derive(Debug, Clone, PartialEq)]
+pub struct InstalledPackage {
+    pub record: PackageRecord,
+    pub reason: PackageReason,
+    pub provides: Vec<String>,
+    pub requires: Vec<String>,
+    pub recommends: Vec<String>,
+    /// the files of the package required by other installed packages (Ex. `/usr/bin/sh`)
+    pub files: Vec<String>,
+}
+
+impl InstalledPackage {
+    /// attributes that must be requested from Rpm.list() to build an InstalledPackage
+    pub fn attrs() -> Vec<PackageAttr> {
+        let mut attrs = PackageRecord::attrs();
+        attrs.extend([
+            PackageAttr::Reason,
+            PackageAttr::Provides,
+            PackageAttr::Requires,
+            PackageAttr::Recommends,
+        ]);
+        attrs
+    }
+
+    /// build an InstalledPackage from a HashMap contains values returned from call to Rpm.list() method
+    /// with the attributes returned by [`InstalledPackage::attrs`], the files are optional
+    pub fn from(pkg: &HashMap<String, OwnedValue>) -> Result<InstalledPackage> {
+        Ok(Self {
+            record: PackageRecord::from(pkg)?,
+            reason: PackageReason::from(from_variant!(pkg, String, "reason")?.as_str()),
+            provides: from_variant!(pkg, Vec<String>, "provides")?,
+            requires: from_variant!(pkg, Vec<String>, "requires")?,
+            recommends: from_variant!(pkg, Vec<String>, "recommends")?,
+            files: optional_variant(pkg, "Rpm.list()", "files")?.unwrap_or_default(),
+        })
+    }
+
+    /// package name
+    pub fn name(&self) -> &str {
+        &self.record.name
+    }
+}
+
+/// The capability names used by a dependency string.
+/// `foo >= 1.0` gives `[foo]` and a rich dependency like `(foo if bar)` gives `[foo, bar]`
+pub fn capability_names(dep: &str) -> Vec<&str> {
+    let dep = dep.trim();
+    if !dep.starts_with('(') {
+        return dep.split_whitespace().take(1).collect();
+    }
+    const KEYWORDS: &[&str] = &["and", "or", "if", "else", "with", "without", "unless"];
+    const OPERATORS: &[&str] = &["<", "<=", "=", ">=", ">"];
+    let mut names = Vec::new();
+    let mut tokens = dep.split_whitespace();
+    while let Some(token) = tokens.next() {
+        // strip the parentheses belonging to the rich dependency, not the capability (Ex. perl(Foo))
+        let mut token = token.trim_start_matches('(');
+        while token.ends_with(')') && token.matches(')').count() > token.matches('(').count() {
+            token = &token[..token.len() - 1];
+        }
+        if OPERATORS.contains(&token) {
+            // skip the version after the operator
+            tokens.next();
+        } else if !token.is_empty() && !KEYWORDS.contains(&token) {
+            names.push(token);
+        }
+    }
+    names
+}
+
+// endregion: --- InstalledPackage
+
+// region:    --- InstalledPackages
+
+/// The installed packages and the dependencies between them,
+/// used to find leaf packages and dependencies that is no longer needed.
+#[derive(Debug, Clone)]
+pub struct InstalledPackages {
+    packages: Vec<InstalledPackage>,
+    /// index of the installed packages required by each package
+    requires: Vec<HashSet<usize>>,
+    /// index of the installed packages recommended by each package
+    recommends: Vec<HashSet<usize>>,
+}
+
+impl InstalledPackages {
+    /// Build the dependencies between the installed packages
+    pub fn new(packages: Vec<InstalledPackage>) -> Self {
+        let mut providers: HashMap<&str, Vec<usize>> = HashMap::new();
+        for (idx, pkg) in packages.iter().enumerate() {
+            providers.entry(pkg.name()).or_default().push(idx);
+            for provide in &pkg.provides {
+                for name in capability_names(provide) {
+                    providers.entry(name).or_default().push(idx);
+                }
+            }
+            for file in &pkg.files {
+                providers.entry(file.as_str()).or_default().push(idx);
+            }
+        }
+        let resolve = |idx: usize, deps: &[String]| -> HashSet<usize> {
+            deps.iter()
+                .flat_map(|dep| capability_names(dep))
+                .filter_map(|name| providers.get(name))
+                .flatten()
+                .copied()
+                .filter(|provider| *provider != idx)
+                .collect()
+        };
+        let requires = packages
+            .iter()
+            .enumerate()
+            .map(|(idx, pkg)| resolve(idx, &pkg.requires))
+            .collect();
+        let recommends = packages
+            .iter()
+            .enumerate()
+            .map(|(idx, pkg)| resolve(idx, &pkg.recommends))
+            .collect();
+        Self {
+            packages,
+            requires,
+            recommends,
+        }
+    }
+
+    /// Load all installed packages by calling org.rpm.dnf.v0.rpm.Rpm.list()
+    pub async fn load(daemon: impl AsRef<DnfDaemon>) -> Result<Self> {
+        let options = ListOptions::builder()
+            .attrs(InstalledPackage::attrs())
+            .scope(Scope::Installed)
+            .build();
+        let pkgs = daemon.as_ref().rpm.list(options.to_dbus()).await.context(RPM, "list")?;
+        let mut packages = Vec::new();
+        for pkg in &pkgs.items {
+            packages.push(InstalledPackage::from(pkg)?);
+        }
+        fill_required_files(daemon.as_ref(), &mut packages).await?;
+        Ok(Self::new(packages))
+    }
+
+    /// All the installed packages
+    pub fn packages(&self) -> &[InstalledPackage] {
+        &self.packages
+    }
+
+    /// The installed packages grouped by the reason they was installed
+    pub fn by_reason(&self) -> BTreeMap<PackageReason, Vec<&InstalledPackage>> {
+        let mut groups: BTreeMap<PackageReason, Vec<&InstalledPackage>> = BTreeMap::new();
+        for pkg in &self.packages {
+            groups.entry(pkg.reason.clone()).or_default().push(pkg);
+        }
+        groups
+    }
+
+    /// Leaf packages, the packages not required by any other installed package
+    pub fn leaves(&self) -> Vec<&InstalledPackage> {
+        let required: HashSet<usize> = self.requires.iter().flatten().copied().collect();
+        self.sorted((0..self.packages.len()).filter(|idx| !required.contains(idx)).collect())
+    }
+
+    /// Packages installed as a dependency, that are not required or recommended
+    /// (directly or indirectly) by any package installed by the user, a group or an external tool.
+    pub fn unneeded(&self) -> Vec<&InstalledPackage> {
+        let mut needed: HashSet<usize> = HashSet::new();
+        let mut queue: VecDeque<usize> = (0..self.packages.len())
+            .filter(|idx| !self.packages[*idx].reason.is_dependency())
+            .collect();
+        while let Some(idx) = queue.pop_front() {
+            if needed.insert(idx) {
+                queue.extend(self.requires[idx].iter().chain(self.recommends[idx].iter()));
+            }
+        }
+        self.sorted((0..self.packages.len()).filter(|idx| !needed.contains(idx)).collect())
+    }
+
+    /// the packages with the given index, sorted by name
+    fn sorted(&self, idxs: Vec<usize>) -> Vec<&InstalledPackage> {
+        let mut pkgs: Vec<&InstalledPackage> = idxs.into_iter().map(|idx| &self.packages[idx]).collect();
+        pkgs.sort_by(|a, b| a.name().cmp(b.name()));
+        pkgs
+    }
+}
+
+/// The files required or recommended by the packages (dependencies starting with `/`)
+pub fn required_files(packages: &[InstalledPackage]) -> Vec<String> {
+    let mut files: Vec<String> = packages
+        .iter()
+        .flat_map(|pkg| pkg.requires.iter().chain(pkg.recommends.iter()))
+        .flat_map(|dep| capability_names(dep))
+        .filter(|name| name.starts_with('/'))
+        .map(str::to_string)
+        .collect();
+    files.sort();
+    files.dedup();
+    files
+}
+
+/// Find the installed packages providing the required files, with a Rpm.list() matching the file names.
+/// Listing the files of all installed packages is slow and returns a lot of data.
+async fn fill_required_files(daemon: &DnfDaemon, packages: &mut [InstalledPackage]) -> Result<()> {
+    let files = required_files(packages);
+    if files.is_empty() {
+        return Ok(());
+    }
+    let mut attrs = PackageRecord::attrs();
+    attrs.push(PackageAttr::Files);
+    let options = ListOptions::builder()
+        .attrs(attrs)
+        .patterns(&files)
+        .scope(Scope::Installed)
+        .with_filenames(true)
+        .build();
+    let pkgs = daemon.rpm.list(options.to_dbus()).await.context(RPM, "list")?;
+    for pkg in &pkgs.items {
+        let nevra = PackageRecord::from(pkg)?.nevra();
+        let provided: Vec<String> = from_variant!(pkg, Vec<String>, "files")?;
+        if let Some(installed) = packages.iter_mut().find(|installed| installed.record.nevra() == nevra) {
+            installed.files = provided.into_iter().filter(|file| files.contains(file)).collect();
+        }
+    }
+    Ok(())
+}
+
+// endregion: --- InstalledPackages
+
+/// Build a transaction that removes all installed dependencies, that is no longer needed
+/// by any package installed by the user.
+/// dnf5daemon has no autoremove over the DBus API, so the unneeded packages are found by
+/// [`InstalledPackages::unneeded`] and queued for removal.
+pub async fn autoremove(daemon: &DnfDaemon) -> Result<Transaction<'_>> {
+    let installed = InstalledPackages::load(daemon).await?;
+    let specs: Vec<String> = installed.unneeded().iter().map(|pkg| pkg.record.nevra()).collect();
+    let mut transaction = Transaction::new(daemon);
+    if !specs.is_empty() {
+        transaction.remove(&specs).await?;
+    }
+    Ok(transaction)
+}
+
+// region:    --- Unit Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zbus::zvariant::Value;
+
+    fn pkg(name: &str, reason: PackageReason, requires: &[&str], provides: &[&str]) -> InstalledPackage {
+        InstalledPackage {
+            record: PackageRecord {
+                name: name.to_string(),
+                epoch: "0".to_string(),
+                version: "1.0".to_string(),
+                release: "1.fc39".to_string(),
+                arch: "x86_64".to_string(),
+                repo_id: "@System".to_string(),
+                is_installed: true,
+                sourcerpm: None,
+            },
+            reason,
+            provides: provides.iter().map(|p| p.to_string()).collect(),
+            requires: requires.iter().map(|r| r.to_string()).collect(),
+            recommends: Vec::new(),
+            files: Vec::new(),
+        }
+    }
+
+    fn names(pkgs: Vec<&InstalledPackage>) -> Vec<&str> {
+        pkgs.into_iter().map(|pkg| pkg.name()).collect()
+    }
+
+    fn installed() -> InstalledPackages {
+        use PackageReason::*;
+        let mut bash = pkg("bash", Dependency, &[], &["bash = 5.2"]);
+        bash.files.push("/usr/bin/sh".to_string());
+        let mut app = pkg("app", User, &["libfoo.so.1()(64bit)", "/usr/bin/sh"], &[]);
+        app.recommends.push("app-docs".to_string());
+        InstalledPackages::new(vec![
+            app,
+            pkg("libfoo", Dependency, &["libbar >= 1.0"], &["libfoo.so.1()(64bit)"]),
+            pkg("libbar", Dependency, &[], &["libbar = 1.0"]),
+            pkg("app-docs", WeakDependency, &[], &[]),
+            pkg("old-lib", Dependency, &["libbar"], &[]),
+            pkg("orphan", Clean, &[], &[]),
+            bash,
+            pkg("grouppkg", Group, &["(perl(Foo) or libbar)"], &[]),
+        ])
+    }
+
+    #[test]
+    fn capability_names_plain_and_rich() {
+        assert_eq!(capability_names("libbar >= 1.0"), vec!["libbar"]);
+        assert_eq!(capability_names("libfoo.so.1()(64bit)"), vec!["libfoo.so.1()(64bit)"]);
+        assert_eq!(capability_names("(foo if bar)"), vec!["foo", "bar"]);
+        assert_eq!(
+            capability_names("(perl(Foo::Bar) >= 1.2 or (baz with qux))"),
+            vec!["perl(Foo::Bar)", "baz", "qux"]
+        );
+    }
+
+    #[test]
+    fn group_by_reason() {
+        let installed = installed();
+        let groups = installed.by_reason();
+        assert_eq!(names(groups[&PackageReason::User].clone()), vec!["app"]);
+        assert_eq!(
+            names(groups[&PackageReason::Dependency].clone()),
+            vec!["libfoo", "libbar", "old-lib", "bash"]
+        );
+        assert_eq!(names(groups[&PackageReason::Group].clone()), vec!["grouppkg"]);
+        assert!(!groups.contains_key(&PackageReason::External));
+    }
+
+    #[test]
+    fn leaf_packages() {
+        let installed = installed();
+        // recommends do not count, only requires
+        assert_eq!(
+            names(installed.leaves()),
+            vec!["app", "app-docs", "grouppkg", "old-lib", "orphan"]
+        );
+    }
+
+    #[test]
+    fn unneeded_packages() {
+        let installed = installed();
+        // libfoo, libbar, bash (file dep) and app-docs (recommended) are still needed by app
+        assert_eq!(names(installed.unneeded()), vec!["old-lib", "orphan"]);
+    }
+
+    #[test]
+    fn required_file_dependencies() {
+        let mut app = pkg(
+            "app",
+            PackageReason::User,
+            &["/usr/bin/sh", "libfoo", "(/usr/bin/perl or perl)"],
+            &[],
+        );
+        app.recommends.push("/usr/bin/sh".to_string());
+        let tool = pkg("tool", PackageReason::User, &["/usr/bin/python3"], &[]);
+        assert_eq!(
+            required_files(&[app, tool]),
+            vec!["/usr/bin/perl", "/usr/bin/python3", "/usr/bin/sh"]
+        );
+    }
+
+    #[test]
+    fn installed_package_from() {
+        let mut data = HashMap::new();
+        for (key, value) in [
+            ("name", "app"),
+            ("epoch", "0"),
+            ("version", "1.0"),
+            ("release", "1"),
+            ("arch", "noarch"),
+            ("repo_id", "@System"),
+            ("sourcerpm", "app-1.0-1.src.rpm"),
+            ("reason", "Weak Dependency"),
+        ] {
+            data.insert(key.to_string(), Value::new(value).try_into_owned().unwrap());
+        }
+        data.insert("is_installed".to_string(), Value::new(true).try_into_owned().unwrap());
+        for key in ["provides", "requires", "recommends", "files"] {
+            let value = Value::new(vec![format!("{key}-value")]);
+            data.insert(key.to_string(), value.try_into_owned().unwrap());
+        }
+        let pkg = InstalledPackage::from(&data).unwrap();
+        assert_eq!(pkg.name(), "app");
+        assert_eq!(pkg.reason, PackageReason::WeakDependency);
+        assert_eq!(pkg.requires, vec!["requires-value".to_string()]);
+        assert_eq!(pkg.files, vec!["files-value".to_string()]);
+
+        data.remove("files");
+        assert!(InstalledPackage::from(&data).unwrap().files.is_empty());
+        data.remove("requires");
+        assert!(InstalledPackage::from(&data).is_err());
+    }
+}
+
+// endregion: --- Unit Tests
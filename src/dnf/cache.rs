@@ -0,0 +1,154 @@
+use crate::package::{DnfPackage, ListOptions};
+use std::collections::HashMap;
+
+// region:    --- CacheStats
+
+/// Hit/miss statistics for the query cache
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct CacheStats {
+    /// number of queries answered from the cache
+    pub hits: u64,
+    /// number of queries that had to call Rpm.list()
+    pub misses: u64,
+    /// number of times the cache has been cleared
+    pub invalidations: u64,
+}
+
+impl CacheStats {
+    /// fraction of queries answered from the cache (0.0 if no queries has been made)
+    pub fn hit_ratio(&self) -> f64 {
+        let total = self.hits + self.misses;
+        if total == 0 {
+            0.0
+        } else {
+            self.hits as f64 / total as f64
+        }
+    }
+}
+
+// endregion: --- CacheStats
+
+// region:    --- QueryCache
+
+/// Cache for results of org.rpm.dnf.v0.rpm.Rpm.list(), keyed on the [`ListOptions`] used for the query.
+///
+/// The cache is owned by a [`crate::DnfDaemon`] session and is cleared, when something
+/// that can change the result happens through the same session
+/// (read_all_repos, Base.reset, enabling/disabling repos or a completed transaction).
+#[derive(Debug, Default)]
+pub struct QueryCache {
+    entries: HashMap<ListOptions, Vec<DnfPackage>>,
+    stats: CacheStats,
+}
+
+impl QueryCache {
+    /// Create a new empty cache
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Lookup the packages for a query, updating the hit/miss statistics
+    pub fn get(&mut self, options: &ListOptions) -> Option<Vec<DnfPackage>> {
+        match self.entries.get(options) {
+            Some(packages) => {
+                self.stats.hits += 1;
+                Some(packages.clone())
+            }
+            None => {
+                self.stats.misses += 1;
+                None
+            }
+        }
+    }
+
+    /// Store the packages returned for a query
+    pub fn insert(&mut self, options: ListOptions, packages: Vec<DnfPackage>) {
+        self.entries.insert(options, packages);
+    }
+
+    /// Remove all cached queries
+    pub fn invalidate(&mut self) {
+        self.entries.clear();
+        self.stats.invalidations += 1;
+    }
+
+    /// Number of cached queries
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check if the cache contains no queries
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Current hit/miss statistics
+    pub fn stats(&self) -> CacheStats {
+        self.stats
+    }
+}
+
+// endregion: --- QueryCache
+
+// region:    --- Unit Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::package::{PackageAttr, Scope};
+
+    fn options(scope: Scope) -> ListOptions {
+        ListOptions::builder()
+            .attrs(vec![PackageAttr::Name])
+            .patterns(&vec!["dnf5*".to_string()])
+            .scope(scope)
+            .build()
+    }
+
+    fn package(name: &str) -> DnfPackage {
+        DnfPackage {
+            name: name.to_string(),
+            arch: "x86_64".to_string(),
+            evr: "1.0-1".to_string(),
+            repo_id: "fedora".to_string(),
+            is_installed: false,
+            size: 1024,
+        }
+    }
+
+    #[test]
+    fn cache_hit_and_miss() {
+        let mut cache = QueryCache::new();
+        assert!(cache.is_empty());
+        assert_eq!(cache.get(&options(Scope::All)), None);
+
+        cache.insert(options(Scope::All), vec![package("dnf5")]);
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache.get(&options(Scope::All)), Some(vec![package("dnf5")]));
+        // same patterns, but another scope is another query
+        assert_eq!(cache.get(&options(Scope::Installed)), None);
+
+        let stats = cache.stats();
+        assert_eq!(stats.hits, 1);
+        assert_eq!(stats.misses, 2);
+        assert!((stats.hit_ratio() - 1.0 / 3.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn cache_invalidate() {
+        let mut cache = QueryCache::new();
+        cache.insert(options(Scope::All), vec![package("dnf5")]);
+        cache.invalidate();
+        assert!(cache.is_empty());
+        assert_eq!(cache.get(&options(Scope::All)), None);
+        assert_eq!(cache.stats().invalidations, 1);
+    }
+
+    #[test]
+    fn cache_stats_empty() {
+        let stats = CacheStats::default();
+        assert_eq!(stats.hit_ratio(), 0.0);
+    }
+}
+
+// endregion: --- Unit Tests
@@ -1,28 +1,40 @@
 #![allow(unused_imports)]
 use log::{debug, info, trace, warn};
 use std::collections::HashMap;
+use std::sync::Mutex;
 
 use zbus::{Connection, zvariant::OwnedObjectPath};
 
 use crate::dnf;
+use crate::dnf::cache::{CacheStats, QueryCache};
+use crate::dnf::options::{SessionOptions, as_refs};
+use crate::dnf::proxy::{BASE, REPO, SESSION_MANAGER};
+use crate::errors::DbusContext;
+use crate::package::{DnfPackage, ListOptions};
 use crate::{Error, Result};
 
 /// This does all the work by creating a new session to the dnf5daemon-server.
 /// Store proxies to the Dbus interfaces publised be the dnf5daemon-server.
 /// Automatic close the session, when the instance is dropped.
 /// So no session will be kept running on the dnf5daemon is the user application panics.
+///
+/// An opt-in query cache for `get_packages` can be enabled with [`DnfDaemon::enable_cache`].
+/// The repositories are changed with the `read_all_repos`, `reset`, `enable_repos` and `disable_repos`
+/// methods on this struct, so the cache is invalidated when needed.
 #[derive(Debug)]
 pub struct DnfDaemon {
     /// proxy for interface org.rpm.dnf.v0.SessionManger
     session_manager: dnf::proxy::SessionManagerProxy<'static>,
     path: OwnedObjectPath,
-    /// proxy for interface org.rpm.dnf.v0.Base
-    pub base: dnf::proxy::BaseProxy<'static>,
+    /// proxy for interface org.rpm.dnf.v0.Base (use the methods on DnfDaemon, they invalidate the cache)
+    pub(crate) base: dnf::proxy::BaseProxy<'static>,
     /// proxy for interface org.rpm.dnf.v0.Rpm
+    /// calls made directly on it bypass the query cache, and don't invalidate it
     pub rpm: dnf::proxy::RpmProxy<'static>,
-    /// proxy for interface org.rpm.dnf.v0.Repo
-    pub repo: dnf::proxy::RepoProxy<'static>,
+    /// proxy for interface org.rpm.dnf.v0.Repo (use the methods on DnfDaemon, they invalidate the cache)
+    pub(crate) repo: dnf::proxy::RepoProxy<'static>,
     /// proxy for interface org.rpm.dnf.v0.Goal
+    /// a transaction executed directly with it doesn't invalidate the query cache, use [`crate::transaction::Transaction`]
     pub goal: dnf::proxy::GoalProxy<'static>,
     /// proxy for interface org.rpm.dnf.v0.Group
     pub group: dnf::proxy::GroupProxy<'static>,
@@ -32,6 +44,8 @@ pub struct DnfDaemon {
     pub advisory: dnf::proxy::AdvisoryProxy<'static>,
     /// session connect status
     connected: bool,
+    /// query cache for get_packages (None when disabled)
+    cache: Mutex<Option<QueryCache>>,
 }
 
 impl AsRef<DnfDaemon> for DnfDaemon {
@@ -41,74 +55,71 @@ impl AsRef<DnfDaemon> for DnfDaemon {
 }
 /// methods to open/close the connection to dnf5daemon-server and setup proxies for the used interfaces
 impl DnfDaemon {
+    /// open a session with the default configuration of dnf5daemon-server
     pub async fn default() -> Result<DnfDaemon> {
+        Self::with_options(SessionOptions::default()).await
+    }
+
+    /// open a session with the given options (Ex. the releasever to use)
+    pub async fn with_options(options: SessionOptions) -> Result<DnfDaemon> {
+        options.validate()?;
         let connection = Connection::system().await?;
 
         // proxy for interface org.rpm.dnf.v0.SessionManger
         let proxy = dnf::proxy::SessionManagerProxy::new(&connection).await?;
 
-        let path = proxy.open_session(HashMap::new()).await?;
+        let options = options.to_dbus();
+        let path = proxy
+            .open_session(as_refs(&options))
+            .await
+            .context(SESSION_MANAGER, "open_session")?;
 
         // proxy for interface org.rpm.dnf.v0.Base
         let base = dnf::proxy::BaseProxy::builder(&connection)
-            .path(path.clone())
-            .unwrap()
-            .destination("org.rpm.dnf.v0")
-            .unwrap()
+            .path(path.clone())?
+            .destination("org.rpm.dnf.v0")?
             .build()
             .await?;
 
         // proxy for interface org.rpm.dnf.v0.Rpm
         let rpm = dnf::proxy::RpmProxy::builder(&connection)
-            .path(path.clone())
-            .unwrap()
-            .destination("org.rpm.dnf.v0")
-            .unwrap()
+            .path(path.clone())?
+            .destination("org.rpm.dnf.v0")?
             .build()
             .await?;
 
         // proxy for interface org.rpm.dnf.v0.Repo
         let repo = dnf::proxy::RepoProxy::builder(&connection)
-            .path(path.clone())
-            .unwrap()
-            .destination("org.rpm.dnf.v0")
-            .unwrap()
+            .path(path.clone())?
+            .destination("org.rpm.dnf.v0")?
             .build()
             .await?;
 
         // proxy for interface org.rpm.dnf.v0.Goal
         let goal = dnf::proxy::GoalProxy::builder(&connection)
-            .path(path.clone())
-            .unwrap()
-            .destination("org.rpm.dnf.v0")
-            .unwrap()
+            .path(path.clone())?
+            .destination("org.rpm.dnf.v0")?
             .build()
             .await?;
 
         // proxy for interface org.rpm.dnf.v0.Group
         let group = dnf::proxy::GroupProxy::builder(&connection)
-            .path(path.clone())
-            .unwrap()
-            .destination("org.rpm.dnf.v0")
-            .unwrap()
+            .path(path.clone())?
+            .destination("org.rpm.dnf.v0")?
             .build()
             .await?;
 
         // proxy for interface org.rpm.dnf.v0.Advisory
         let advisory = dnf::proxy::AdvisoryProxy::builder(&connection)
-            .path(path.clone())
-            .unwrap()
-            .destination("org.rpm.dnf.v0")
-            .unwrap()
+            .path(path.clone())?
+            .destination("org.rpm.dnf.v0")?
             .build()
             .await?;
 
         // proxy for interface org.rpm.dnf.v0.Offline
         let offline = dnf::proxy::OfflineProxy::builder(&connection)
-            .path(path.clone())
-            .unwrap()
-            .destination("org.rpm.dnf.v0")
-            .unwrap()
+            .path(path.clone())?
+            .destination("org.rpm.dnf.v0")?
             .build()
             .await?;
         debug!("DBUS: org.rpm.dnf.v0 session opened : {path}");
@@ -123,6 +134,7 @@ impl DnfDaemon {
             offline,
             advisory,
             connected: true,
+            cache: Mutex::new(None),
         })
     }
 
@@ -130,7 +142,10 @@ impl DnfDaemon {
     pub async fn close(&mut self) -> Result<bool> {
         if self.connected {
             let obj_path = self.path.as_ref();
-            self.session_manager.close_session(&obj_path).await?;
+            self.session_manager
+                .close_session(&obj_path)
+                .await
+                .context(SESSION_MANAGER, "close_session")?;
             self.connected = false;
             Ok(self.connected)
         } else {
@@ -142,6 +157,87 @@ impl DnfDaemon {
     pub fn is_connected(&self) -> bool {
         self.connected.to_owned()
     }
+
+    /// the object path of the session, signals from dnf5daemon-server contains it to tell which session they belong to
+    pub fn session_path(&self) -> &OwnedObjectPath {
+        &self.path
+    }
+
+    /// load the repositories metadata by calling org.rpm.dnf.v0.Base.read_all_repos, and invalidate the query cache
+    pub async fn read_all_repos(&self) -> Result<bool> {
+        let rc = self.base.read_all_repos().await.context(BASE, "read_all_repos")?;
+        self.invalidate_cache();
+        Ok(rc)
+    }
+
+    /// reset the session by calling org.rpm.dnf.v0.Base.reset, and invalidate the query cache
+    pub async fn reset(&self) -> Result<(bool, String)> {
+        let rc = self.base.reset().await.context(BASE, "reset")?;
+        self.invalidate_cache();
+        Ok(rc)
+    }
+
+    /// enable repositories by calling org.rpm.dnf.v0.rpm.Repo.enable, and invalidate the query cache
+    pub async fn enable_repos(&self, repo_ids: &[&str]) -> Result<()> {
+        self.repo.enable(repo_ids).await.context(REPO, "enable")?;
+        self.invalidate_cache();
+        Ok(())
+    }
+
+    /// disable repositories by calling org.rpm.dnf.v0.rpm.Repo.disable, and invalidate the query cache
+    pub async fn disable_repos(&self, repo_ids: &[&str]) -> Result<()> {
+        self.repo.disable(repo_ids).await.context(REPO, "disable")?;
+        self.invalidate_cache();
+        Ok(())
+    }
+}
+
+/// methods to control the query cache used by get_packages
+impl DnfDaemon {
+    /// enable the query cache, does nothing if it is already enabled
+    pub fn enable_cache(&self) {
+        if let Ok(mut cache) = self.cache.lock()
+            && cache.is_none()
+        {
+            *cache = Some(QueryCache::new());
+        }
+    }
+
+    /// disable the query cache and drop the cached results
+    pub fn disable_cache(&self) {
+        if let Ok(mut cache) = self.cache.lock() {
+            *cache = None;
+        }
+    }
+
+    /// remove all cached results
+    pub fn invalidate_cache(&self) {
+        if let Ok(mut cache) = self.cache.lock()
+            && let Some(cache) = cache.as_mut()
+        {
+            debug!("query cache invalidated");
+            cache.invalidate();
+        }
+    }
+
+    /// hit/miss statistics for the query cache (None when the cache is disabled)
+    pub fn cache_stats(&self) -> Option<CacheStats> {
+        self.cache.lock().ok()?.as_ref().map(|cache| cache.stats())
+    }
+
+    /// lookup a query in the cache
+    pub(crate) fn cached_packages(&self, options: &ListOptions) -> Option<Vec<DnfPackage>> {
+        self.cache.lock().ok()?.as_mut()?.get(options)
+    }
+
+    /// store the result of a query in the cache
+    pub(crate) fn cache_packages(&self, options: ListOptions, packages: &[DnfPackage]) {
+        if let Ok(mut cache) = self.cache.lock()
+            && let Some(cache) = cache.as_mut()
+        {
+            cache.insert(options, packages.to_vec());
+        }
+    }
 }
 
 impl Drop for DnfDaemon {
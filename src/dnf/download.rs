@@ -0,0 +1,557 @@
+use crate::DnfDaemon;
+use crate::Result;
+use crate::dnf::progress::{session_signals, signal_event};
+use futures::Stream;
+use futures::future;
+use futures::stream::BoxStream;
+use futures_util::StreamExt;
+use std::future::Future;
+use std::pin::pin;
+use std::task::Poll;
+use std::time::{Duration, Instant};
+use zbus::message::Message;
+
+// region:    --- Enums
+
+/// Status of a finished download, as reported in the download_end signal
+// -- check dnf5 source code:
+// -- https://github.com/rpm-software-management/dnf5/blob/main/include/libdnf5/repo/download_callbacks.hpp
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransferStatus {
+    Successful,
+    AlreadyExists,
+    Error,
+    Unknown(u32),
+}
+
+impl From<u32> for TransferStatus {
+    fn from(code: u32) -> Self {
+        match code {
+            0 => TransferStatus::Successful,
+            1 => TransferStatus::AlreadyExists,
+            2 => TransferStatus::Error,
+            _ => TransferStatus::Unknown(code),
+        }
+    }
+}
+
+/// Download progress, made from the download_* signals of org.rpm.dnf.v0.Base
+#[derive(Debug, Clone, PartialEq)]
+pub enum DownloadEvent {
+    /// a new download is started (Ex. repository metadata or a package)
+    AddNew {
+        download_id: String,
+        description: String,
+        total: i64,
+    },
+    Progress {
+        download_id: String,
+        total: i64,
+        downloaded: i64,
+    },
+    End {
+        download_id: String,
+        status: TransferStatus,
+        message: String,
+    },
+    MirrorFailure(MirrorFailure),
+}
+
+impl DownloadEvent {
+    fn download_id(&self) -> &str {
+        match self {
+            DownloadEvent::AddNew { download_id, .. }
+            | DownloadEvent::Progress { download_id, .. }
+            | DownloadEvent::End { download_id, .. } => download_id,
+            DownloadEvent::MirrorFailure(failure) => &failure.download_id,
+        }
+    }
+}
+
+// endregion: --- Enums
+
+// region:    --- DownloadState
+
+/// A mirror that failed for a download, the download continues with the next mirror
+#[derive(Debug, Clone, PartialEq)]
+pub struct MirrorFailure {
+    pub download_id: String,
+    pub message: String,
+    pub url: String,
+    pub metadata: String,
+}
+
+/// The progress of a single download
+#[derive(Debug, Clone, PartialEq)]
+pub struct DownloadItem {
+    pub download_id: String,
+    pub description: String,
+    /// bytes to download, 0 or less when the size is unknown
+    pub total: i64,
+    pub downloaded: i64,
+    /// the status of the download, when it has ended
+    pub status: Option<TransferStatus>,
+    pub message: Option<String>,
+    started: Instant,
+    updated: Instant,
+}
+
+impl DownloadItem {
+    fn new(download_id: String, description: String, total: i64, now: Instant) -> Self {
+        Self {
+            download_id,
+            description,
+            total,
+            downloaded: 0,
+            status: None,
+            message: None,
+            started: now,
+            updated: now,
+        }
+    }
+
+    /// Check if the download has ended
+    pub fn is_finished(&self) -> bool {
+        self.status.is_some()
+    }
+
+    /// Download rate in bytes per second
+    pub fn rate(&self) -> f64 {
+        rate(self.downloaded, self.updated.duration_since(self.started))
+    }
+
+    /// Estimated time left, None if the size or the rate is unknown
+    pub fn eta(&self) -> Option<Duration> {
+        if self.is_finished() {
+            return Some(Duration::ZERO);
+        }
+        eta(self.total - self.downloaded, self.total, self.rate())
+    }
+}
+
+/// bytes per second
+fn rate(bytes: i64, elapsed: Duration) -> f64 {
+    let secs = elapsed.as_secs_f64();
+    if secs > 0.0 { bytes.max(0) as f64 / secs } else { 0.0 }
+}
+
+/// time to download the remaining bytes with the given rate
+fn eta(remaining: i64, total: i64, rate: f64) -> Option<Duration> {
+    if total <= 0 || rate <= 0.0 {
+        return None;
+    }
+    Some(Duration::from_secs_f64(remaining.max(0) as f64 / rate))
+}
+
+/// The progress of all downloads seen by a [`DownloadMonitor`]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DownloadState {
+    items: Vec<DownloadItem>,
+    mirror_failures: Vec<MirrorFailure>,
+    /// progress and end events received before the download was added
+    early: Vec<DownloadEvent>,
+    started: Option<Instant>,
+    updated: Option<Instant>,
+}
+
+impl DownloadState {
+    /// Update the state with a download event received at the given time
+    pub fn apply(&mut self, event: DownloadEvent, now: Instant) {
+        self.started.get_or_insert(now);
+        self.updated = Some(now);
+        match event {
+            DownloadEvent::AddNew {
+                download_id,
+                description,
+                total,
+            } => {
+                // a late download_add_new doesn't restart a download that has ended
+                if self.item(&download_id).is_some_and(DownloadItem::is_finished) {
+                    return;
+                }
+                self.items.retain(|item| item.download_id != download_id);
+                self.items.push(DownloadItem::new(download_id.clone(), description, total, now));
+                let (early, rest) = std::mem::take(&mut self.early)
+                    .into_iter()
+                    .partition(|event| event.download_id() == download_id);
+                self.early = rest;
+                for event in early {
+                    self.apply(event, now);
+                }
+            }
+            DownloadEvent::Progress { .. } | DownloadEvent::End { .. } if self.item(event.download_id()).is_none() => {
+                self.early.push(event);
+            }
+            DownloadEvent::Progress {
+                download_id,
+                total,
+                downloaded,
+            } => {
+                if let Some(item) = self.item_mut(&download_id) {
+                    item.total = total;
+                    item.downloaded = downloaded;
+                    item.updated = now;
+                }
+            }
+            DownloadEvent::End {
+                download_id,
+                status,
+                message,
+            } => {
+                if let Some(item) = self.item_mut(&download_id) {
+                    if status != TransferStatus::Error && item.total > 0 {
+                        item.downloaded = item.total;
+                    }
+                    item.status = Some(status);
+                    item.message = (!message.is_empty()).then_some(message);
+                    item.updated = now;
+                }
+            }
+            DownloadEvent::MirrorFailure(failure) => self.mirror_failures.push(failure),
+        }
+    }
+
+    fn item_mut(&mut self, download_id: &str) -> Option<&mut DownloadItem> {
+        self.items.iter_mut().find(|item| item.download_id == download_id)
+    }
+
+    /// All downloads, in the order they was started
+    pub fn items(&self) -> &[DownloadItem] {
+        &self.items
+    }
+
+    /// A download by its id
+    pub fn item(&self, download_id: &str) -> Option<&DownloadItem> {
+        self.items.iter().find(|item| item.download_id == download_id)
+    }
+
+    /// The mirrors that failed
+    pub fn mirror_failures(&self) -> &[MirrorFailure] {
+        &self.mirror_failures
+    }
+
+    /// The downloads that ended with an error
+    pub fn failed(&self) -> Vec<&DownloadItem> {
+        self.items
+            .iter()
+            .filter(|item| item.status == Some(TransferStatus::Error))
+            .collect()
+    }
+
+    /// Total bytes to download, for the downloads with a known size
+    pub fn total_bytes(&self) -> i64 {
+        self.items.iter().map(|item| item.total.max(0)).sum()
+    }
+
+    /// Bytes downloaded
+    pub fn downloaded_bytes(&self) -> i64 {
+        self.items.iter().map(|item| item.downloaded.max(0)).sum()
+    }
+
+    /// Aggregate download rate in bytes per second
+    pub fn rate(&self) -> f64 {
+        match (self.started, self.updated) {
+            (Some(started), Some(updated)) => rate(self.downloaded_bytes(), updated.duration_since(started)),
+            _ => 0.0,
+        }
+    }
+
+    /// Estimated time left for all downloads, None if the size or the rate is unknown
+    pub fn eta(&self) -> Option<Duration> {
+        let total = self.total_bytes();
+        eta(total - self.downloaded_bytes(), total, self.rate())
+    }
+
+    /// Check if all started downloads has ended
+    pub fn is_finished(&self) -> bool {
+        self.items.iter().all(|item| item.is_finished())
+    }
+}
+
+// endregion: --- DownloadState
+
+// region:    --- DownloadMonitor
+
+/// Follow the downloads made by dnf5daemon-server for a session, while running a call that
+/// downloads (Ex. `read_all_repos` for metadata or `execute` on a transaction for packages).
+///
+/// ``` no_run
+/// # use dnf5daemon::download::DownloadMonitor;
+/// # use dnf5daemon::{DnfDaemon, Result};
+/// # async fn load(dnf_daemon: &DnfDaemon) -> Result<()> {
+/// let mut monitor = DownloadMonitor::new(dnf_daemon).await?;
+/// monitor
+///     .run(dnf_daemon.read_all_repos(), |state| {
+///         println!("{} of {} bytes", state.downloaded_bytes(), state.total_bytes());
+///     })
+///     .await?;
+/// for failure in monitor.state().mirror_failures() {
+///     println!("mirror failed : {} ({})", failure.url, failure.message);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct DownloadMonitor {
+    events: BoxStream<'static, DownloadEvent>,
+    state: DownloadState,
+}
+
+impl DownloadMonitor {
+    /// Subscribe to the download signals for the session of the daemon
+    pub async fn new(daemon: &DnfDaemon) -> Result<Self> {
+        let session = daemon.session_path();
+        let messages = session_signals(daemon.base.inner(), session).await?;
+        Ok(Self {
+            events: decode_download_events(messages, session.to_string()),
+            state: DownloadState::default(),
+        })
+    }
+
+    /// The progress of the downloads seen so far
+    pub fn state(&self) -> &DownloadState {
+        &self.state
+    }
+
+    /// Run the future, while following the downloads. `on_update` is called each time the state changes.
+    pub async fn run<F: Future>(&mut self, fut: F, mut on_update: impl FnMut(&DownloadState)) -> F::Output {
+        let mut fut = pin!(fut);
+        futures::future::poll_fn(|cx| {
+            // signals send before the call returned are already queued, so they are handled first
+            self.drain(cx, &mut on_update);
+            let output = fut.as_mut().poll(cx);
+            if output.is_ready() {
+                // signals that arrived while the future was polled the last time (Ex. the last download_end)
+                self.drain(cx, &mut on_update);
+            }
+            output
+        })
+        .await
+    }
+
+    /// Apply the events that are ready
+    fn drain(&mut self, cx: &mut std::task::Context<'_>, on_update: &mut impl FnMut(&DownloadState)) {
+        while let Poll::Ready(Some(event)) = self.events.poll_next_unpin(cx) {
+            self.state.apply(event, Instant::now());
+            on_update(&self.state);
+        }
+    }
+}
+
+/// Decode the signal messages into download events, in the order they are received
+fn decode_download_events(
+    messages: impl Stream<Item = zbus::Result<Message>> + Send + 'static,
+    session: String,
+) -> BoxStream<'static, DownloadEvent> {
+    messages
+        .filter_map(move |msg| future::ready(msg.ok().and_then(|msg| download_event(&msg, &session))))
+        .boxed()
+}
+
+/// Decode a download_* signal of org.rpm.dnf.v0.Base for the session into an event
+fn download_event(msg: &Message, session: &str) -> Option<DownloadEvent> {
+    let header = msg.header();
+    match header.member()?.as_str() {
+        "download_add_new" => signal_event!(msg, session, (download_id: &str, description: &str, total: i64) => {
+            DownloadEvent::AddNew {
+                download_id: download_id.to_string(),
+                description: description.to_string(),
+                total,
+            }
+        }),
+        "download_progress" => signal_event!(msg, session, (download_id: &str, total: i64, downloaded: i64) => {
+            DownloadEvent::Progress {
+                download_id: download_id.to_string(),
+                total,
+                downloaded,
+            }
+        }),
+        "download_end" => signal_event!(msg, session, (download_id: &str, status: u32, message: &str) => {
+            DownloadEvent::End {
+                download_id: download_id.to_string(),
+                status: status.into(),
+                message: message.to_string(),
+            }
+        }),
+        "download_mirror_failure" => {
+            signal_event!(msg, session, (download_id: &str, message: &str, url: &str, metadata: &str) => {
+                DownloadEvent::MirrorFailure(MirrorFailure {
+                    download_id: download_id.to_string(),
+                    message: message.to_string(),
+                    url: url.to_string(),
+                    metadata: metadata.to_string(),
+                })
+            })
+        }
+        _ => None,
+    }
+}
+
+// endregion: --- DownloadMonitor
+
+// region:    --- Unit Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn add(id: &str, total: i64) -> DownloadEvent {
+        DownloadEvent::AddNew {
+            download_id: id.to_string(),
+            description: format!("{id} description"),
+            total,
+        }
+    }
+
+    fn progress(id: &str, total: i64, downloaded: i64) -> DownloadEvent {
+        DownloadEvent::Progress {
+            download_id: id.to_string(),
+            total,
+            downloaded,
+        }
+    }
+
+    fn end(id: &str, status: TransferStatus) -> DownloadEvent {
+        DownloadEvent::End {
+            download_id: id.to_string(),
+            status,
+            message: String::new(),
+        }
+    }
+
+    #[test]
+    fn transfer_status_from_code() {
+        assert_eq!(TransferStatus::from(0), TransferStatus::Successful);
+        assert_eq!(TransferStatus::from(1), TransferStatus::AlreadyExists);
+        assert_eq!(TransferStatus::from(2), TransferStatus::Error);
+        assert_eq!(TransferStatus::from(7), TransferStatus::Unknown(7));
+    }
+
+    #[test]
+    fn state_aggregates_downloads() {
+        let start = Instant::now();
+        let mut state = DownloadState::default();
+        state.apply(add("fedora", 1000), start);
+        state.apply(add("updates", 3000), start);
+        state.apply(progress("fedora", 1000, 500), start + Duration::from_secs(1));
+        state.apply(progress("updates", 3000, 1500), start + Duration::from_secs(1));
+
+        assert_eq!(state.items().len(), 2);
+        assert_eq!(state.total_bytes(), 4000);
+        assert_eq!(state.downloaded_bytes(), 2000);
+        assert_eq!(state.rate(), 2000.0);
+        assert_eq!(state.eta(), Some(Duration::from_secs(1)));
+        let fedora = state.item("fedora").unwrap();
+        assert_eq!(fedora.rate(), 500.0);
+        assert_eq!(fedora.eta(), Some(Duration::from_secs(1)));
+        assert!(!state.is_finished());
+
+        state.apply(
+            end("fedora", TransferStatus::Successful),
+            start + Duration::from_secs(2),
+        );
+        state.apply(end("updates", TransferStatus::Error), start + Duration::from_secs(2));
+        assert!(state.is_finished());
+        assert_eq!(state.item("fedora").unwrap().downloaded, 1000);
+        assert_eq!(state.item("fedora").unwrap().eta(), Some(Duration::ZERO));
+        let failed: Vec<&str> = state.failed().iter().map(|item| item.download_id.as_str()).collect();
+        assert_eq!(failed, vec!["updates"]);
+    }
+
+    #[test]
+    fn state_unknown_size_and_mirror_failures() {
+        let start = Instant::now();
+        let mut state = DownloadState::default();
+        state.apply(add("pkg", -1), start);
+        state.apply(progress("pkg", -1, 100), start + Duration::from_secs(1));
+        assert_eq!(state.total_bytes(), 0);
+        assert_eq!(state.eta(), None);
+        assert_eq!(state.item("pkg").unwrap().eta(), None);
+
+        // events for unknown downloads are not added as downloads
+        state.apply(progress("other", 10, 5), start);
+        assert!(state.item("other").is_none());
+
+        let failure = MirrorFailure {
+            download_id: "pkg".to_string(),
+            message: "Curl error (28): Timeout was reached".to_string(),
+            url: "https://mirror.example.com/fedora".to_string(),
+            metadata: String::new(),
+        };
+        state.apply(DownloadEvent::MirrorFailure(failure.clone()), start);
+        assert_eq!(state.mirror_failures(), &[failure]);
+    }
+
+    #[test]
+    fn state_events_before_add_new() {
+        let start = Instant::now();
+        let mut state = DownloadState::default();
+        state.apply(progress("fedora", 1000, 400), start);
+        state.apply(end("fedora", TransferStatus::Successful), start);
+        assert!(state.items().is_empty());
+        // the early events are applied, when the download is added
+        state.apply(add("fedora", 1000), start);
+        let fedora = state.item("fedora").unwrap();
+        assert_eq!(fedora.status, Some(TransferStatus::Successful));
+        assert_eq!(fedora.downloaded, 1000);
+        assert!(state.is_finished());
+
+        // a late add_new doesn't restart an ended download
+        state.apply(add("fedora", 1000), start);
+        assert_eq!(state.items().len(), 1);
+        assert!(state.is_finished());
+    }
+
+    #[tokio::test]
+    async fn decode_download_signals() {
+        let session = zbus::zvariant::ObjectPath::try_from("/org/rpm/dnf/v0/session/1").unwrap();
+        let other = zbus::zvariant::ObjectPath::try_from("/org/rpm/dnf/v0/session/2").unwrap();
+        let signal = |member: &'static str| Message::signal(session.as_str(), "org.rpm.dnf.v0.Base", member).unwrap();
+        let messages = vec![
+            signal("download_add_new").build(&(&session, "fedora", "Fedora 42", 1000i64)),
+            signal("download_progress").build(&(&other, "fedora", 1000i64, 100i64)),
+            signal("download_progress").build(&(&session, "fedora", 1000i64, 500i64)),
+            signal("download_end").build(&(&session, "fedora", 2u32, "Curl error")),
+        ];
+        let events: Vec<DownloadEvent> = decode_download_events(futures::stream::iter(messages), session.to_string())
+            .collect()
+            .await;
+        assert_eq!(
+            events,
+            vec![
+                DownloadEvent::AddNew {
+                    download_id: "fedora".to_string(),
+                    description: "Fedora 42".to_string(),
+                    total: 1000,
+                },
+                progress("fedora", 1000, 500),
+                DownloadEvent::End {
+                    download_id: "fedora".to_string(),
+                    status: TransferStatus::Error,
+                    message: "Curl error".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn run_applies_events_send_before_the_call_returned() {
+        let (sender, receiver) = futures::channel::mpsc::unbounded();
+        let mut monitor = DownloadMonitor {
+            events: receiver.boxed(),
+            state: DownloadState::default(),
+        };
+        sender.unbounded_send(add("fedora", 1000)).unwrap();
+        // the download_end arrives while the call is polled the last time
+        let call = futures::future::lazy(|_| {
+            sender.unbounded_send(end("fedora", TransferStatus::Successful)).unwrap();
+            42
+        });
+        let mut updates = 0;
+        let output = futures::executor::block_on(monitor.run(call, |_| updates += 1));
+        assert_eq!(output, 42);
+        assert_eq!(updates, 2);
+        assert!(monitor.state().is_finished());
+    }
+}
+
+// endregion: --- Unit Tests
@@ -0,0 +1,260 @@
+use std::cmp::Ordering;
+
+// region:    --- rpmvercmp
+
+/// Compare two version (or release) strings the same way as rpm does.
+// -- port of rpmvercmp() from rpm source code:
+// -- https://github.com/rpm-software-management/rpm/blob/master/rpmio/rpmvercmp.cc
+pub fn rpmvercmp(a: &str, b: &str) -> Ordering {
+    if a == b {
+        return Ordering::Equal;
+    }
+    let one = a.as_bytes();
+    let two = b.as_bytes();
+    let (mut i, mut j) = (0, 0);
+    let is_sep = |c: u8| !c.is_ascii_alphanumeric() && c != b'~' && c != b'^';
+
+    while i < one.len() || j < two.len() {
+        while i < one.len() && is_sep(one[i]) {
+            i += 1;
+        }
+        while j < two.len() && is_sep(two[j]) {
+            j += 1;
+        }
+        let c1 = one.get(i).copied();
+        let c2 = two.get(j).copied();
+
+        // tilde sorts before everything else
+        if c1 == Some(b'~') || c2 == Some(b'~') {
+            if c1 != Some(b'~') {
+                return Ordering::Greater;
+            }
+            if c2 != Some(b'~') {
+                return Ordering::Less;
+            }
+            i += 1;
+            j += 1;
+            continue;
+        }
+
+        // caret sorts after the end of the string, but before everything else
+        if c1 == Some(b'^') || c2 == Some(b'^') {
+            if c1.is_none() {
+                return Ordering::Less;
+            }
+            if c2.is_none() {
+                return Ordering::Greater;
+            }
+            if c1 != Some(b'^') {
+                return Ordering::Greater;
+            }
+            if c2 != Some(b'^') {
+                return Ordering::Less;
+            }
+            i += 1;
+            j += 1;
+            continue;
+        }
+
+        // if we ran to the end of either, we are finished with the loop
+        let (Some(c1), Some(_)) = (c1, c2) else {
+            break;
+        };
+
+        // grab the next segment of digits or letters from both strings
+        let is_num = c1.is_ascii_digit();
+        let in_segment = |c: &u8| {
+            if is_num {
+                c.is_ascii_digit()
+            } else {
+                c.is_ascii_alphabetic()
+            }
+        };
+        let seg1_len = one[i..].iter().take_while(|c| in_segment(c)).count();
+        let seg2_len = two[j..].iter().take_while(|c| in_segment(c)).count();
+        let mut seg1 = &one[i..i + seg1_len];
+        let mut seg2 = &two[j..j + seg2_len];
+        i += seg1_len;
+        j += seg2_len;
+
+        // segments of different types, numeric is newer than alpha
+        if seg2.is_empty() {
+            return if is_num { Ordering::Greater } else { Ordering::Less };
+        }
+
+        if is_num {
+            // leading zeros don't count, then the longest number wins
+            while seg1.first() == Some(&b'0') {
+                seg1 = &seg1[1..];
+            }
+            while seg2.first() == Some(&b'0') {
+                seg2 = &seg2[1..];
+            }
+            match seg1.len().cmp(&seg2.len()) {
+                Ordering::Equal => {}
+                other => return other,
+            }
+        }
+        match seg1.cmp(seg2) {
+            Ordering::Equal => {}
+            other => return other,
+        }
+    }
+
+    match (i >= one.len(), j >= two.len()) {
+        (true, true) => Ordering::Equal,
+        (true, false) => Ordering::Less,
+        _ => Ordering::Greater,
+    }
+}
+
+// endregion: --- rpmvercmp
+
+// region:    --- Evr
+
+/// Epoch, version and release of a package, ordered and compared the same way as rpm does
+/// (Ex. `1.05` equals `1.5`), so it doesn't implement Hash
+#[derive(Debug, Clone)]
+pub struct Evr {
+    pub epoch: u32,
+    pub version: String,
+    pub release: String,
+}
+
+impl Evr {
+    /// New Evr from the epoch, version and release strings (an empty epoch is 0)
+    pub fn new(epoch: &str, version: &str, release: &str) -> Self {
+        Self {
+            epoch: epoch.parse().unwrap_or(0),
+            version: version.to_string(),
+            release: release.to_string(),
+        }
+    }
+
+    /// Parse a `[epoch:]version[-release]` string (Ex. `1:3.0.7-2.fc38`)
+    pub fn parse(evr: &str) -> Self {
+        let (epoch, vr) = match evr.split_once(':') {
+            Some((epoch, vr)) => (epoch, vr),
+            None => ("0", evr),
+        };
+        let (version, release) = vr.rsplit_once('-').unwrap_or((vr, ""));
+        Self::new(epoch, version, release)
+    }
+}
+
+impl Ord for Evr {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.epoch
+            .cmp(&other.epoch)
+            .then_with(|| rpmvercmp(&self.version, &other.version))
+            .then_with(|| rpmvercmp(&self.release, &other.release))
+    }
+}
+
+impl PartialEq for Evr {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Evr {}
+
+impl PartialOrd for Evr {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl core::fmt::Display for Evr {
+    /// epoch:version-release, the epoch is left out if it is 0 (same format as dnf5 uses)
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.epoch != 0 {
+            write!(f, "{}:", self.epoch)?;
+        }
+        write!(f, "{}", self.version)?;
+        if !self.release.is_empty() {
+            write!(f, "-{}", self.release)?;
+        }
+        Ok(())
+    }
+}
+
+// endregion: --- Evr
+
+// region:    --- Unit Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Ordering::*;
+
+    #[test]
+    fn rpmvercmp_cases() {
+        // test cases from the rpm test suite (tests/rpmvercmp.at)
+        let cases = [
+            ("1.0", "1.0", Equal),
+            ("1.0", "2.0", Less),
+            ("2.0.1", "2.0", Greater),
+            ("2.0.1a", "2.0.1", Greater),
+            ("5.5p1", "5.5p2", Less),
+            ("5.5p10", "5.5p1", Greater),
+            ("10xyz", "10.1xyz", Less),
+            ("xyz10", "xyz10.1", Less),
+            ("xyz.4", "8", Less),
+            ("8", "xyz.4", Greater),
+            ("1.0010", "1.9", Greater),
+            ("1.05", "1.5", Equal),
+            ("1.0", "1", Greater),
+            ("2.50", "2.5", Greater),
+            ("fc4", "fc.4", Equal),
+            ("FC5", "fc4", Less),
+            ("2a", "2.0", Less),
+            ("1.0~rc1", "1.0", Less),
+            ("1.0~rc1", "1.0~rc2", Less),
+            ("1.0~rc1~git123", "1.0~rc1", Less),
+            ("1.0^", "1.0", Greater),
+            ("1.0^git1", "1.0", Greater),
+            ("1.0^git1", "1.01", Less),
+            ("1.0^git1", "1.0^git2", Less),
+            ("1.0^git1~pre", "1.0^git1", Less),
+        ];
+        for (a, b, expected) in cases {
+            assert_eq!(rpmvercmp(a, b), expected, "rpmvercmp({a}, {b})");
+            assert_eq!(rpmvercmp(b, a), expected.reverse(), "rpmvercmp({b}, {a})");
+        }
+    }
+
+    #[test]
+    fn evr_parse_and_display() {
+        let evr = Evr::parse("1:3.0.7-2.fc38");
+        assert_eq!(evr, Evr::new("1", "3.0.7", "2.fc38"));
+        assert_eq!(evr.to_string(), "1:3.0.7-2.fc38");
+        let evr = Evr::parse("6.5.6-300.fc39");
+        assert_eq!(evr.epoch, 0);
+        assert_eq!(evr.to_string(), "6.5.6-300.fc39");
+        assert_eq!(Evr::parse("1.0").to_string(), "1.0");
+    }
+
+    #[test]
+    fn evr_ordering() {
+        assert!(Evr::parse("6.5.6-300.fc39") < Evr::parse("6.5.12-300.fc39"));
+        assert!(Evr::parse("6.5.6-300.fc39") < Evr::parse("6.5.6-301.fc39"));
+        // epoch wins over version
+        assert!(Evr::parse("1:1.0-1") > Evr::parse("2.0-1"));
+        let mut evrs = [Evr::parse("6.10.0-1"), Evr::parse("6.9.2-1"), Evr::parse("6.10.0~rc1-1")];
+        evrs.sort();
+        let sorted: Vec<String> = evrs.iter().map(|e| e.to_string()).collect();
+        assert_eq!(sorted, vec!["6.9.2-1", "6.10.0~rc1-1", "6.10.0-1"]);
+    }
+
+    #[test]
+    fn evr_equality() {
+        // equal evrs are equal with ==, so == agrees with the ordering
+        assert_eq!(Evr::parse("0:1.0-1"), Evr::parse("1.0-1"));
+        assert_eq!(Evr::parse("1.05-1"), Evr::parse("1.5-1"));
+        assert_eq!(Evr::parse("fc4-1"), Evr::parse("fc.4-1"));
+        assert_ne!(Evr::parse("1.0-1"), Evr::parse("1.0-2"));
+    }
+}
+
+// endregion: --- Unit Tests
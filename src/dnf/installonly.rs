@@ -0,0 +1,258 @@
+use crate::DnfDaemon;
+use crate::dnf::evr::Evr;
+use crate::package::{ListOptions, PackageRecord, Scope, list_package_records};
+use crate::transaction::Transaction;
+use crate::{Error, Result};
+use log::debug;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+// region:    --- Constants
+
+/// Default installonly packages, the same as the default `installonlypkgs` in dnf5
+/// The entries are matched against both package names and provides.
+// -- check dnf5 source code:
+// -- https://github.com/rpm-software-management/dnf5/blob/main/include/libdnf5/conf/const.hpp
+pub const DEFAULT_INSTALLONLY_PKGS: &[&str] = &[
+    "kernel",
+    "kernel-PAE",
+    "installonlypkg(kernel)",
+    "installonlypkg(kernel-module)",
+    "installonlypkg(vm)",
+    "multiversion(kernel)",
+];
+
+/// File containing the release of the running kernel (same as `uname -r`)
+pub const OSRELEASE_PATH: &str = "/proc/sys/kernel/osrelease";
+
+// endregion: --- Constants
+
+// region:    --- InstallonlyGroup
+
+/// All installed versions of an installonly package
+#[derive(Debug, Clone, PartialEq)]
+pub struct InstallonlyGroup {
+    pub name: String,
+    /// installed versions sorted by EVR, oldest first
+    pub installed: Vec<PackageRecord>,
+}
+
+impl InstallonlyGroup {
+    /// the newest installed version
+    pub fn newest(&self) -> Option<&PackageRecord> {
+        self.installed.last()
+    }
+
+    /// the versions to remove, to only keep the newest `keep` versions.
+    /// the version matching the running kernel release is never returned.
+    pub fn removals(&self, keep: usize, running: Option<&str>) -> Vec<&PackageRecord> {
+        let old = self.installed.len().saturating_sub(keep);
+        self.installed[..old]
+            .iter()
+            .filter(|pkg| !running.is_some_and(|release| is_running(pkg, release)))
+            .collect()
+    }
+}
+
+/// Check if a package is build for the running kernel release (`version-release.arch`)
+fn is_running(pkg: &PackageRecord, release: &str) -> bool {
+    let vr = format!("{}-{}", pkg.version, pkg.release);
+    release == vr || release == format!("{vr}.{}", pkg.arch)
+}
+
+/// Read the running kernel release (Ex. 6.5.6-300.fc39.x86_64) from a file like /proc/sys/kernel/osrelease
+pub fn read_running_kernel(path: impl AsRef<Path>) -> Result<String> {
+    let release = std::fs::read_to_string(path)?;
+    Ok(release.trim().to_string())
+}
+
+/// Group installed packages by name, with the versions sorted by EVR
+pub fn group_installonly(records: Vec<PackageRecord>) -> Vec<InstallonlyGroup> {
+    let mut groups: BTreeMap<String, Vec<PackageRecord>> = BTreeMap::new();
+    for record in records.into_iter().filter(|pkg| pkg.is_installed) {
+        groups.entry(record.name.clone()).or_default().push(record);
+    }
+    groups
+        .into_iter()
+        .map(|(name, mut installed)| {
+            installed.sort_by_cached_key(|pkg| Evr::new(&pkg.epoch, &pkg.version, &pkg.release));
+            InstallonlyGroup { name, installed }
+        })
+        .collect()
+}
+
+// endregion: --- InstallonlyGroup
+
+// region:    --- Installonly
+
+/// Handling of installonly packages (Ex. kernels), where several versions can be installed
+/// at the same time.
+///
+/// ``` no_run
+/// # use dnf5daemon::installonly::Installonly;
+/// # use dnf5daemon::{DnfDaemon, Result};
+/// # async fn cleanup(dnf_daemon: &DnfDaemon) -> Result<()> {
+/// // remove all kernels, except the 2 newest and the running one
+/// let transaction = Installonly::new(dnf_daemon).cleanup(2).await?;
+/// transaction.resolve().await?.execute().await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct Installonly<'a> {
+    dnf_daemon: &'a DnfDaemon,
+    packages: Vec<String>,
+    osrelease_path: PathBuf,
+}
+
+impl<'a> Installonly<'a> {
+    /// New Installonly using the default installonly packages and running kernel detection
+    pub fn new(dnf_daemon: &'a DnfDaemon) -> Self {
+        Self {
+            dnf_daemon,
+            packages: DEFAULT_INSTALLONLY_PKGS.iter().map(|pkg| pkg.to_string()).collect(),
+            osrelease_path: PathBuf::from(OSRELEASE_PATH),
+        }
+    }
+
+    /// Set the package names or provides to handle as installonly
+    pub fn packages(mut self, packages: Vec<String>) -> Self {
+        self.packages = packages;
+        self
+    }
+
+    /// Set the file to read the running kernel release from (default: /proc/sys/kernel/osrelease)
+    pub fn osrelease_path(mut self, path: impl AsRef<Path>) -> Self {
+        self.osrelease_path = path.as_ref().to_path_buf();
+        self
+    }
+
+    /// The release of the running kernel (Ex. 6.5.6-300.fc39.x86_64)
+    pub fn running_kernel(&self) -> Result<String> {
+        read_running_kernel(&self.osrelease_path)
+    }
+
+    /// The installed installonly packages grouped by name, with versions sorted by EVR
+    pub async fn groups(&self) -> Result<Vec<InstallonlyGroup>> {
+        let options = ListOptions::builder()
+            .attrs(PackageRecord::attrs())
+            .patterns(&self.packages)
+            .scope(Scope::Installed)
+            .with_provides(true)
+            .build();
+        let records = list_package_records(self.dnf_daemon, &options).await?;
+        Ok(group_installonly(records))
+    }
+
+    /// The installed packages to remove, to only keep the newest `keep` versions of each
+    /// installonly package. The running kernel is never included.
+    pub async fn cleanup_plan(&self, keep: usize) -> Result<Vec<PackageRecord>> {
+        if keep == 0 {
+            return Err(Error::InvalidArgument(
+                "at least one version of installonly packages must be kept".to_string(),
+            ));
+        }
+        let running = self.running_kernel()?;
+        debug!("running kernel : {running}");
+        let groups = self.groups().await?;
+        Ok(groups
+            .iter()
+            .flat_map(|group| group.removals(keep, Some(&running)))
+            .cloned()
+            .collect())
+    }
+
+    /// Build a transaction that removes all but the newest `keep` versions of each
+    /// installonly package. The running kernel is never removed.
+    pub async fn cleanup(&self, keep: usize) -> Result<Transaction<'a>> {
+        let specs: Vec<String> = self.cleanup_plan(keep).await?.iter().map(|pkg| pkg.nevra()).collect();
+        let mut transaction = Transaction::new(self.dnf_daemon);
+        if !specs.is_empty() {
+            transaction.remove(&specs).await?;
+        }
+        Ok(transaction)
+    }
+}
+
+// endregion: --- Installonly
+
+// region:    --- Unit Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kernel(name: &str, version: &str) -> PackageRecord {
+        PackageRecord {
+            name: name.to_string(),
+            epoch: "0".to_string(),
+            version: version.to_string(),
+            release: "300.fc39".to_string(),
+            arch: "x86_64".to_string(),
+            repo_id: "@System".to_string(),
+            is_installed: true,
+            sourcerpm: Some(format!("kernel-{version}-300.fc39.src.rpm")),
+        }
+    }
+
+    fn versions(pkgs: &[&PackageRecord]) -> Vec<String> {
+        pkgs.iter().map(|pkg| pkg.version.clone()).collect()
+    }
+
+    #[test]
+    fn group_sorted_by_evr() {
+        let groups = group_installonly(vec![
+            kernel("kernel-core", "6.5.12"),
+            kernel("kernel", "6.5.6"),
+            kernel("kernel-core", "6.5.6"),
+            kernel("kernel-core", "6.10.0"),
+            kernel("kernel", "6.5.12"),
+        ]);
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].name, "kernel");
+        assert_eq!(groups[1].name, "kernel-core");
+        let core: Vec<&PackageRecord> = groups[1].installed.iter().collect();
+        assert_eq!(versions(&core), vec!["6.5.6", "6.5.12", "6.10.0"]);
+        assert_eq!(groups[1].newest().unwrap().version, "6.10.0");
+    }
+
+    #[test]
+    fn removals_keep_newest() {
+        let groups = group_installonly(vec![
+            kernel("kernel-core", "6.5.6"),
+            kernel("kernel-core", "6.5.12"),
+            kernel("kernel-core", "6.6.1"),
+            kernel("kernel-core", "6.10.0"),
+        ]);
+        let group = &groups[0];
+        assert_eq!(versions(&group.removals(2, None)), vec!["6.5.6", "6.5.12"]);
+        assert_eq!(versions(&group.removals(3, None)), vec!["6.5.6"]);
+        assert!(group.removals(4, None).is_empty());
+        assert!(group.removals(10, None).is_empty());
+    }
+
+    #[test]
+    fn removals_never_running() {
+        let groups = group_installonly(vec![
+            kernel("kernel-core", "6.5.6"),
+            kernel("kernel-core", "6.5.12"),
+            kernel("kernel-core", "6.6.1"),
+        ]);
+        let group = &groups[0];
+        let running = Some("6.5.6-300.fc39.x86_64");
+        assert_eq!(versions(&group.removals(1, running)), vec!["6.5.12"]);
+        // running release without arch
+        let running = Some("6.5.12-300.fc39");
+        assert_eq!(versions(&group.removals(1, running)), vec!["6.5.6"]);
+    }
+
+    #[test]
+    fn running_kernel_from_path() {
+        let path = std::env::temp_dir().join(format!("dnf5daemon-osrelease-{}", std::process::id()));
+        std::fs::write(&path, "6.5.6-300.fc39.x86_64\n").unwrap();
+        assert_eq!(read_running_kernel(&path).unwrap(), "6.5.6-300.fc39.x86_64");
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(read_running_kernel(&path), Err(Error::Io(_))));
+    }
+}
+
+// endregion: --- Unit Tests
@@ -0,0 +1,275 @@
+use crate::DnfDaemon;
+use crate::Result;
+use crate::dnf::progress::signal_stream;
+use crate::dnf::proxy::REPO;
+use crate::errors::DbusContext;
+use futures::future::Either;
+use futures::stream::BoxStream;
+use futures_util::StreamExt;
+use log::{info, warn};
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::pin;
+use zbus::zvariant::{OwnedValue, Value};
+
+// region:    --- KeyImportRequest
+
+/// A request from dnf5daemon-server to import a repository GPG key, from the repo_key_import_request signal
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyImportRequest {
+    pub key_id: String,
+    pub user_ids: Vec<String>,
+    pub fingerprint: String,
+    /// url the key was read from
+    pub url: String,
+    /// creation time of the key (seconds since the epoch)
+    pub timestamp: i64,
+}
+
+// endregion: --- KeyImportRequest
+
+// region:    --- KeyImportHandler
+
+/// Decide if a repository key should be imported
+///
+/// It is implemented for closures, so a `|request: &KeyImportRequest| -> bool` can be used as handler.
+pub trait KeyImportHandler: Send {
+    /// Return true to import the key
+    fn confirm(&mut self, request: &KeyImportRequest) -> bool;
+
+    /// Options send with the answer by calling org.rpm.dnf.v0.rpm.Repo.confirm_key_with_options.
+    /// Without options (the default) the answer is send by calling org.rpm.dnf.v0.rpm.Repo.confirm_key.
+    fn options(&mut self, _request: &KeyImportRequest) -> HashMap<String, OwnedValue> {
+        HashMap::new()
+    }
+}
+
+impl<F> KeyImportHandler for F
+where
+    F: FnMut(&KeyImportRequest) -> bool + Send,
+{
+    fn confirm(&mut self, request: &KeyImportRequest) -> bool {
+        self(request)
+    }
+}
+
+/// Handler that never imports a key
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AlwaysDeny;
+
+impl KeyImportHandler for AlwaysDeny {
+    fn confirm(&mut self, _request: &KeyImportRequest) -> bool {
+        false
+    }
+}
+
+/// Handler that only imports keys with a known fingerprint
+/// Fingerprints are compared without case and whitespace, so `"A1B2 C3D4"` matches `"a1b2c3d4"`.
+#[derive(Debug, Clone, Default)]
+pub struct FingerprintAllowlist {
+    fingerprints: Vec<String>,
+}
+
+impl FingerprintAllowlist {
+    /// New allowlist with the given fingerprints
+    pub fn new(fingerprints: &[&str]) -> Self {
+        Self {
+            fingerprints: fingerprints.iter().map(|fp| normalize_fingerprint(fp)).collect(),
+        }
+    }
+
+    /// Check if the fingerprint is in the allowlist
+    pub fn contains(&self, fingerprint: &str) -> bool {
+        let fingerprint = normalize_fingerprint(fingerprint);
+        self.fingerprints.contains(&fingerprint)
+    }
+}
+
+impl KeyImportHandler for FingerprintAllowlist {
+    fn confirm(&mut self, request: &KeyImportRequest) -> bool {
+        self.contains(&request.fingerprint)
+    }
+}
+
+fn normalize_fingerprint(fingerprint: &str) -> String {
+    fingerprint
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<String>()
+        .to_uppercase()
+}
+
+// endregion: --- KeyImportHandler
+
+// region:    --- KeyImporter
+
+/// Answer the key import requests for a session, while running a call that can need a new key
+/// (Ex. `read_all_repos` or `execute` on a transaction). Without it dnf5daemon-server waits for an
+/// answer that never comes.
+///
+/// ``` no_run
+/// # use dnf5daemon::key_import::{FingerprintAllowlist, KeyImporter};
+/// # use dnf5daemon::{DnfDaemon, Result};
+/// # async fn load(dnf_daemon: &DnfDaemon) -> Result<()> {
+/// let allowlist = FingerprintAllowlist::new(&["115D F9AE F857 853E E844 5D0A 0727 707E A15B 79CC"]);
+/// let mut importer = KeyImporter::new(dnf_daemon, allowlist).await?;
+/// importer.run(dnf_daemon.read_all_repos()).await??;
+/// # Ok(())
+/// # }
+/// ```
+pub struct KeyImporter<'a, H: KeyImportHandler> {
+    dnf_daemon: &'a DnfDaemon,
+    requests: BoxStream<'static, KeyImportRequest>,
+    handler: H,
+}
+
+impl<'a, H: KeyImportHandler> KeyImporter<'a, H> {
+    /// Subscribe to the key import requests for the session of the daemon
+    pub async fn new(dnf_daemon: &'a DnfDaemon, handler: H) -> Result<Self> {
+        let base = &dnf_daemon.base;
+        let session = dnf_daemon.session_path();
+        let requests = signal_stream!(base, receive_repo_key_import_request, session, |args| {
+            KeyImportRequest {
+                key_id: args.key_id.to_string(),
+                user_ids: args.user_ids.iter().map(|id| id.to_string()).collect(),
+                fingerprint: args.key_fingerprint.to_string(),
+                url: args.key_url.to_string(),
+                timestamp: args.timestamp,
+            }
+        });
+        Ok(Self {
+            dnf_daemon,
+            requests,
+            handler,
+        })
+    }
+
+    /// Run the future, while answering key import requests with the handler.
+    /// An error is returned if a request could not be answered.
+    pub async fn run<F: Future>(&mut self, fut: F) -> Result<F::Output> {
+        let mut fut = pin!(fut);
+        loop {
+            match futures::future::select(self.requests.next(), fut.as_mut()).await {
+                Either::Left((Some(request), _)) => {
+                    self.answer(&request).await?;
+                }
+                Either::Left((None, _)) => return Ok(fut.await),
+                Either::Right((output, _)) => return Ok(output),
+            }
+        }
+    }
+
+    /// Ask the handler and send the answer to dnf5daemon-server by calling org.rpm.dnf.v0.rpm.Repo.confirm_key,
+    /// or confirm_key_with_options if the handler gives options
+    pub async fn answer(&mut self, request: &KeyImportRequest) -> Result<bool> {
+        let confirmed = self.handler.confirm(request);
+        if confirmed {
+            info!(
+                "importing key {} ({}) from {} for {:?}",
+                request.key_id, request.fingerprint, request.url, request.user_ids
+            );
+        } else {
+            warn!(
+                "refused to import key {} ({}) from {} for {:?}",
+                request.key_id, request.fingerprint, request.url, request.user_ids
+            );
+        }
+        let repo = &self.dnf_daemon.repo;
+        match KeyAnswer::from_options(self.handler.options(request)) {
+            KeyAnswer::ConfirmKey => {
+                repo.confirm_key(&request.key_id, confirmed)
+                    .await
+                    .context(REPO, "confirm_key")?;
+            }
+            KeyAnswer::ConfirmKeyWithOptions(options) => {
+                let options: HashMap<&str, &Value<'_>> =
+                    options.iter().map(|(key, value)| (key.as_str(), &**value)).collect();
+                repo.confirm_key_with_options(&request.key_id, confirmed, options)
+                    .await
+                    .context(REPO, "confirm_key_with_options")?;
+            }
+        }
+        Ok(confirmed)
+    }
+}
+
+/// The call used to send the answer to a key import request
+#[derive(Debug, PartialEq)]
+enum KeyAnswer {
+    /// org.rpm.dnf.v0.rpm.Repo.confirm_key
+    ConfirmKey,
+    /// org.rpm.dnf.v0.rpm.Repo.confirm_key_with_options, with the options from the handler
+    ConfirmKeyWithOptions(HashMap<String, OwnedValue>),
+}
+
+impl KeyAnswer {
+    /// confirm_key_with_options is only used, when the handler gives options
+    fn from_options(options: HashMap<String, OwnedValue>) -> Self {
+        if options.is_empty() {
+            KeyAnswer::ConfirmKey
+        } else {
+            KeyAnswer::ConfirmKeyWithOptions(options)
+        }
+    }
+}
+
+// endregion: --- KeyImporter
+
+// region:    --- Unit Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(fingerprint: &str) -> KeyImportRequest {
+        KeyImportRequest {
+            key_id: "A15B79CC".to_string(),
+            user_ids: vec!["Fedora (40) <fedora-40-primary@fedoraproject.org>".to_string()],
+            fingerprint: fingerprint.to_string(),
+            url: "file:///etc/pki/rpm-gpg/RPM-GPG-KEY-fedora-40-x86_64".to_string(),
+            timestamp: 1_691_000_000,
+        }
+    }
+
+    #[test]
+    fn fingerprint_allowlist() {
+        let mut allowlist = FingerprintAllowlist::new(&["115D F9AE F857 853E E844 5D0A 0727 707E A15B 79CC"]);
+        assert!(allowlist.confirm(&request("115DF9AEF857853EE8445D0A0727707EA15B79CC")));
+        assert!(allowlist.confirm(&request("115df9aef857853ee8445d0a0727707ea15b79cc")));
+        assert!(!allowlist.confirm(&request("0000F9AEF857853EE8445D0A0727707EA15B79CC")));
+        assert!(!FingerprintAllowlist::default().contains("115DF9AEF857853EE8445D0A0727707EA15B79CC"));
+    }
+
+    #[test]
+    fn always_deny_and_closure_handlers() {
+        let req = request("115DF9AEF857853EE8445D0A0727707EA15B79CC");
+        assert!(!AlwaysDeny.confirm(&req));
+
+        let mut asked = Vec::new();
+        let mut handler = |request: &KeyImportRequest| {
+            asked.push(request.key_id.clone());
+            request.url.starts_with("file://")
+        };
+        assert!(handler.confirm(&req));
+        // confirm_key is used, when the handler gives no options
+        assert_eq!(KeyAnswer::from_options(handler.options(&req)), KeyAnswer::ConfirmKey);
+        assert_eq!(asked, vec!["A15B79CC"]);
+    }
+
+    #[test]
+    fn answer_with_options() {
+        let mut options = HashMap::new();
+        let value = Value::new("A15B79CC").try_into_owned().unwrap();
+        options.insert("key_id".to_string(), value);
+        match KeyAnswer::from_options(options) {
+            KeyAnswer::ConfirmKeyWithOptions(options) => {
+                let key_id: String = options["key_id"].clone().try_into().unwrap();
+                assert_eq!(key_id, "A15B79CC");
+            }
+            KeyAnswer::ConfirmKey => panic!("expected confirm_key_with_options"),
+        }
+        assert_eq!(KeyAnswer::from_options(HashMap::new()), KeyAnswer::ConfirmKey);
+    }
+}
+
+// endregion: --- Unit Tests
@@ -1,12 +1,54 @@
+/// This module contains analysis of installed packages by reason and an autoremove transaction.
+pub mod autoremove;
+
+/// This module contains a cache for package queries made via the Dbus API.
+pub mod cache;
+
 /// This module contains a struct and methods to handle the connection to dnf5daemon-server.
 pub mod daemon;
 
+/// This module contains a monitor for the downloads made by dnf5daemon-server.
+pub mod download;
+
+/// This module contains rpm version comparison and a struct for epoch, version and release.
+pub mod evr;
+
+/// This module contains a high level API for installonly packages (Ex. kernels).
+pub mod installonly;
+
+/// This module contains handling of repository key import requests.
+pub mod key_import;
+
+/// This module contains typed options for the transaction related Dbus calls.
+pub mod options;
+
 /// This module contain functions and struct the is a more high level abstation to
 /// using the low-levet DNF5 DBus API
 pub mod package;
 
+/// This module contains policies, that can refuse a resolved transaction before it is executed.
+pub mod policy;
+
+/// This module contains typed problems for transactions that can't be resolved.
+pub mod problem;
+
+/// This module contains typed progress events for running transactions.
+pub mod progress;
+
 /// This module contains Traits for the DBus interfaces that maps the Dbus API of dnf5daemon-server.
 pub(crate) mod proxy;
 
+/// This module contains validation of local rpm files and urls to install.
+pub mod rpm_file;
+
+/// This module contains functions to group binary packages by the source rpm they are build from.
+pub mod source;
+
+/// This module contains a summary of resolved transactions.
+pub mod summary;
+
+/// This module contains a high level API for upgrading the system to a new release.
+pub mod system_upgrade;
+
 /// This module contains sruct and methods for handling a DNF transactions via the Dbus API.
 pub mod transaction;
@@ -0,0 +1,382 @@
+use crate::transaction::OperationKind;
+use crate::{Error, Result};
+use std::collections::{BTreeMap, HashMap};
+use zbus::zvariant::Value;
+
+// region:    --- Types
+
+// -- Custom type for the a{sv} options used in the org.rpm.dnf.v0.rpm.Rpm and org.rpm.dnf.v0.Goal method calls
+pub(crate) type DbusOptions = HashMap<&'static str, Value<'static>>;
+
+/// Borrow the options in the form the proxy methods takes them
+pub(crate) fn as_refs(options: &DbusOptions) -> HashMap<&'static str, &Value<'static>> {
+    options.iter().map(|(key, value)| (*key, value)).collect()
+}
+
+// endregion: --- Types
+
+// region:    --- ActionOptions
+
+/// Options for the org.rpm.dnf.v0.rpm.Rpm methods used to queue packages in a transaction
+/// (install, remove, upgrade, reinstall). Options that are not set, are not send to
+/// dnf5daemon-server, so the server configuration is used.
+// -- check dnf5 source code:
+// -- https://github.com/rpm-software-management/dnf5/blob/main/dnf5daemon-server/services/rpm/rpm.cpp
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ActionOptions {
+    pub skip_broken: Option<bool>,
+    pub skip_unavailable: Option<bool>,
+    pub best: Option<bool>,
+    pub clean_requirements_on_remove: Option<bool>,
+    pub repo_ids: Vec<String>,
+    pub from_repo_ids: Vec<String>,
+    pub installonly_limit: Option<u32>,
+}
+
+impl ActionOptions {
+    /// New ActionOptions with nothing set
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Skip packages with broken dependencies
+    pub fn skip_broken(mut self, skip_broken: bool) -> Self {
+        self.skip_broken = Some(skip_broken);
+        self
+    }
+
+    /// Skip specs that don't match any package
+    pub fn skip_unavailable(mut self, skip_unavailable: bool) -> Self {
+        self.skip_unavailable = Some(skip_unavailable);
+        self
+    }
+
+    /// Only use the best candidates
+    pub fn best(mut self, best: bool) -> Self {
+        self.best = Some(best);
+        self
+    }
+
+    /// Also remove dependencies that are no longer needed (only for remove)
+    pub fn clean_requirements_on_remove(mut self, clean: bool) -> Self {
+        self.clean_requirements_on_remove = Some(clean);
+        self
+    }
+
+    /// Only use packages from these repositories
+    pub fn repo_ids(mut self, repo_ids: &[&str]) -> Self {
+        self.repo_ids = repo_ids.iter().map(|id| id.to_string()).collect();
+        self
+    }
+
+    /// Only act on packages installed from these repositories
+    pub fn from_repo_ids(mut self, from_repo_ids: &[&str]) -> Self {
+        self.from_repo_ids = from_repo_ids.iter().map(|id| id.to_string()).collect();
+        self
+    }
+
+    /// Max number of installed versions of installonly packages (0 is unlimited)
+    pub fn installonly_limit(mut self, limit: u32) -> Self {
+        self.installonly_limit = Some(limit);
+        self
+    }
+
+    /// Check that the options are valid for the given kind of operation
+    pub fn validate(&self, kind: OperationKind) -> Result<()> {
+        if self.clean_requirements_on_remove.is_some() && kind != OperationKind::Remove {
+            return Err(Error::InvalidArgument(format!(
+                "clean_requirements_on_remove can't be used with {kind}"
+            )));
+        }
+        if self.installonly_limit == Some(1) {
+            // dnf refuses a limit of 1, as it would not allow installing a new kernel
+            return Err(Error::InvalidArgument(
+                "installonly_limit must be 0 (unlimited) or at least 2".to_string(),
+            ));
+        }
+        validate_repo_ids("repo_ids", &self.repo_ids)?;
+        validate_repo_ids("from_repo_ids", &self.from_repo_ids)
+    }
+
+    /// Generate a HashMap with key/value (as variant) pairs to use for Dbus
+    pub(crate) fn to_dbus(&self) -> DbusOptions {
+        let mut options = DbusOptions::new();
+        insert_option(&mut options, "skip_broken", self.skip_broken);
+        insert_option(&mut options, "skip_unavailable", self.skip_unavailable);
+        insert_option(&mut options, "best", self.best);
+        insert_option(
+            &mut options,
+            "clean_requirements_on_remove",
+            self.clean_requirements_on_remove,
+        );
+        insert_option(&mut options, "installonly_limit", self.installonly_limit);
+        if !self.repo_ids.is_empty() {
+            options.insert("repo_ids", Value::new(self.repo_ids.clone()));
+        }
+        if !self.from_repo_ids.is_empty() {
+            options.insert("from_repo_ids", Value::new(self.from_repo_ids.clone()));
+        }
+        options
+    }
+}
+
+fn validate_repo_ids(option: &str, repo_ids: &[String]) -> Result<()> {
+    if let Some(id) = repo_ids
+        .iter()
+        .find(|id| id.trim().is_empty() || id.contains(char::is_whitespace))
+    {
+        return Err(Error::InvalidArgument(format!(
+            "{option} contains an invalid repo id: {id:?}"
+        )));
+    }
+    Ok(())
+}
+
+fn insert_option<T: Into<Value<'static>>>(options: &mut DbusOptions, key: &'static str, value: Option<T>) {
+    if let Some(value) = value {
+        options.insert(key, value.into());
+    }
+}
+
+// endregion: --- ActionOptions
+
+// region:    --- ResolveOptions
+
+/// Options for org.rpm.dnf.v0.Goal.resolve()
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ResolveOptions {
+    pub allow_erasing: Option<bool>,
+}
+
+impl ResolveOptions {
+    /// New ResolveOptions with nothing set
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allow removing installed packages to resolve conflicts
+    pub fn allow_erasing(mut self, allow_erasing: bool) -> Self {
+        self.allow_erasing = Some(allow_erasing);
+        self
+    }
+
+    /// Generate a HashMap with key/value (as variant) pairs to use for Dbus
+    pub(crate) fn to_dbus(&self) -> DbusOptions {
+        let mut options = DbusOptions::new();
+        insert_option(&mut options, "allow_erasing", self.allow_erasing);
+        options
+    }
+}
+
+// endregion: --- ResolveOptions
+
+// region:    --- ExecuteOptions
+
+/// Options for org.rpm.dnf.v0.Goal.do_transaction()
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExecuteOptions {
+    pub comment: Option<String>,
+    pub offline: Option<bool>,
+}
+
+impl ExecuteOptions {
+    /// New ExecuteOptions with nothing set
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Comment to store in the transaction history
+    pub fn comment(mut self, comment: &str) -> Self {
+        self.comment = Some(comment.to_string());
+        self
+    }
+
+    /// Prepare the transaction to run at the next boot, instead of running it now
+    pub fn offline(mut self, offline: bool) -> Self {
+        self.offline = Some(offline);
+        self
+    }
+
+    /// Check that the options are valid
+    pub fn validate(&self) -> Result<()> {
+        if let Some(comment) = &self.comment
+            && comment.contains('\0')
+        {
+            return Err(Error::InvalidArgument(
+                "comment can't contain NUL characters".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Generate a HashMap with key/value (as variant) pairs to use for Dbus
+    pub(crate) fn to_dbus(&self) -> DbusOptions {
+        let mut options = DbusOptions::new();
+        insert_option(&mut options, "comment", self.comment.clone());
+        insert_option(&mut options, "offline", self.offline);
+        options
+    }
+}
+
+// endregion: --- ExecuteOptions
+
+// region:    --- SessionOptions
+
+/// Options for org.rpm.dnf.v0.SessionManager.open_session()
+// -- check dnf5 source code:
+// -- https://github.com/rpm-software-management/dnf5/blob/main/dnf5daemon-server/session.cpp
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SessionOptions {
+    pub releasever: Option<String>,
+    pub load_system_repo: Option<bool>,
+    pub load_available_repos: Option<bool>,
+    /// dnf configuration overrides (Ex. `installonly_limit` = `3`)
+    pub config: BTreeMap<String, String>,
+}
+
+impl SessionOptions {
+    /// New SessionOptions with nothing set
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The release version to use for the repositories (Ex. the target release of a system upgrade)
+    pub fn releasever(mut self, releasever: &str) -> Self {
+        self.releasever = Some(releasever.to_string());
+        self
+    }
+
+    /// Load the installed packages
+    pub fn load_system_repo(mut self, load: bool) -> Self {
+        self.load_system_repo = Some(load);
+        self
+    }
+
+    /// Load the metadata of the enabled repositories
+    pub fn load_available_repos(mut self, load: bool) -> Self {
+        self.load_available_repos = Some(load);
+        self
+    }
+
+    /// Override a dnf configuration option for the session
+    pub fn config(mut self, key: &str, value: &str) -> Self {
+        self.config.insert(key.to_string(), value.to_string());
+        self
+    }
+
+    /// Check that the options are valid
+    pub fn validate(&self) -> Result<()> {
+        if let Some(releasever) = &self.releasever
+            && (releasever.is_empty() || releasever.contains(char::is_whitespace))
+        {
+            return Err(Error::InvalidArgument(format!("invalid releasever: {releasever:?}")));
+        }
+        if self.config.keys().any(|key| key.is_empty()) {
+            return Err(Error::InvalidArgument("config keys can't be empty".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Generate a HashMap with key/value (as variant) pairs to use for Dbus
+    pub(crate) fn to_dbus(&self) -> DbusOptions {
+        let mut options = DbusOptions::new();
+        insert_option(&mut options, "releasever", self.releasever.clone());
+        insert_option(&mut options, "load_system_repo", self.load_system_repo);
+        insert_option(&mut options, "load_available_repos", self.load_available_repos);
+        if !self.config.is_empty() {
+            // the config overrides are send as a{sv}
+            let config: HashMap<String, Value<'static>> = self
+                .config
+                .iter()
+                .map(|(key, value)| (key.clone(), Value::new(value.clone())))
+                .collect();
+            options.insert("config", Value::new(config));
+        }
+        options
+    }
+}
+
+// endregion: --- SessionOptions
+
+// region:    --- Unit Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn action_options_to_dbus() {
+        assert!(ActionOptions::new().to_dbus().is_empty());
+        let options = ActionOptions::new()
+            .skip_broken(true)
+            .skip_unavailable(false)
+            .repo_ids(&["fedora", "updates"])
+            .installonly_limit(3)
+            .to_dbus();
+        assert_eq!(options.len(), 4);
+        assert_eq!(options["skip_broken"], Value::new(true));
+        assert_eq!(options["skip_unavailable"], Value::new(false));
+        assert_eq!(options["installonly_limit"], Value::new(3u32));
+        let repo_ids: Vec<String> = options["repo_ids"].clone().try_into().unwrap();
+        assert_eq!(repo_ids, vec!["fedora", "updates"]);
+        assert_eq!(as_refs(&options).len(), 4);
+    }
+
+    #[test]
+    fn action_options_validate() {
+        assert!(ActionOptions::new().validate(OperationKind::Install).is_ok());
+        let options = ActionOptions::new().clean_requirements_on_remove(true);
+        assert!(options.validate(OperationKind::Remove).is_ok());
+        assert!(matches!(
+            options.validate(OperationKind::Install),
+            Err(Error::InvalidArgument(_))
+        ));
+        assert!(
+            ActionOptions::new()
+                .installonly_limit(0)
+                .validate(OperationKind::Install)
+                .is_ok()
+        );
+        assert!(
+            ActionOptions::new()
+                .installonly_limit(1)
+                .validate(OperationKind::Install)
+                .is_err()
+        );
+        assert!(ActionOptions::new().repo_ids(&[""]).validate(OperationKind::Upgrade).is_err());
+        assert!(
+            ActionOptions::new()
+                .from_repo_ids(&["my repo"])
+                .validate(OperationKind::Upgrade)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn resolve_and_execute_options_to_dbus() {
+        assert!(ResolveOptions::new().to_dbus().is_empty());
+        let options = ResolveOptions::new().allow_erasing(true).to_dbus();
+        assert_eq!(options["allow_erasing"], Value::new(true));
+
+        let execute = ExecuteOptions::new().comment("cleanup").offline(true);
+        assert!(execute.validate().is_ok());
+        let options = execute.to_dbus();
+        assert_eq!(options["comment"], Value::new("cleanup"));
+        assert_eq!(options["offline"], Value::new(true));
+        assert!(ExecuteOptions::new().comment("a\0b").validate().is_err());
+    }
+
+    #[test]
+    fn session_options_to_dbus() {
+        assert!(SessionOptions::new().to_dbus().is_empty());
+        let session = SessionOptions::new().releasever("41").config("installonly_limit", "3");
+        assert!(session.validate().is_ok());
+        let options = session.to_dbus();
+        assert_eq!(options["releasever"], Value::new("41"));
+        assert_eq!(options["config"].value_signature(), "a{sv}");
+        assert!(SessionOptions::new().releasever("").validate().is_err());
+        assert!(SessionOptions::new().config("", "1").validate().is_err());
+    }
+}
+
+// endregion: --- Unit Tests
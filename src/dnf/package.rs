@@ -1,26 +1,62 @@
 use crate::dnf::daemon::DnfDaemon;
-use crate::dnf::proxy::ListResults;
+use crate::dnf::proxy::{ListResults, RPM};
+use crate::errors::DbusContext;
 
 use derive_more::From;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use zbus::zvariant::{Error, OwnedValue, Type, Value};
+use zbus::zvariant::{OwnedValue, Type, Value};
 
 // region:    --- macroes
 
 /// Macro to convert a variant store under a given key in a HashMap into a given native type
+/// A missing key or a value of another type gives a [`crate::Error`], telling which field and
+/// dnf5daemon method that failed. (the method defaults to Rpm.list())
 macro_rules! from_variant {
     ($pkg: expr,$typ:ty, $field:literal) => {
+        from_variant!($pkg, $typ, $field, "Rpm.list()")
+    };
+    ($pkg: expr,$typ:ty, $field:literal, $method:expr) => {
         match $pkg.get($field) {
-            Some(v) => <$typ>::try_from(v.to_owned()),
-            None => Err(Error::Message(format!(
-                "Key {:?} not found in Rpm.list() result.",
-                $field
-            ))),
+            Some(v) => <$typ>::try_from(v.to_owned()).map_err(|e| $crate::Error::InvalidFieldType {
+                field: $field.to_string(),
+                method: $method.to_string(),
+                reason: e.to_string(),
+            }),
+            None => Err($crate::Error::MissingField {
+                field: $field.to_string(),
+                method: $method.to_string(),
+            }),
         }
     };
 }
 
+pub(crate) use from_variant;
+
+/// Convert an optional variant stored under a given key in a HashMap into a given native type
+/// A missing key gives None, a value of another type gives a [`crate::Error`], telling which field and
+/// dnf5daemon method that failed.
+pub(crate) fn optional_variant<T>(
+    map: &HashMap<String, OwnedValue>,
+    method: &str,
+    field: &str,
+) -> crate::Result<Option<T>>
+where
+    T: TryFrom<OwnedValue>,
+    T::Error: core::fmt::Display,
+{
+    match map.get(field) {
+        Some(value) => T::try_from(value.to_owned())
+            .map(Some)
+            .map_err(|e| crate::Error::InvalidFieldType {
+                field: field.to_string(),
+                method: method.to_string(),
+                reason: e.to_string(),
+            }),
+        None => Ok(None),
+    }
+}
+
 /// Macro to put a expression into a variant (zvariant::Value)
 macro_rules! to_variant {
     ($var:expr) => {
@@ -49,7 +85,7 @@ macro_rules! insert_field {
 /// Package attributes to use for defining return values from Rpm.list
 // https://dnf5.readthedocs.io/en/latest/dnf_daemon/dnf5daemon_dbus_api.8.html#org.rpm.dnf.v0.rpm.Rpm.list
 
-#[derive(Debug, PartialEq, From, Serialize, Deserialize, Type)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, From, Serialize, Deserialize, Type)]
 pub enum PackageAttr {
     Name,
     Epoch,
@@ -177,7 +213,7 @@ impl core::fmt::Display for PackageAttr {
 /// Package scope for Rpm.List
 // https://dnf5.readthedocs.io/en/latest/dnf_daemon/dnf5daemon_dbus_api.8.html#org.rpm.dnf.v0.rpm.Rpm.list
 
-#[derive(Debug, PartialEq, From, Serialize, Deserialize, Type)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, From, Serialize, Deserialize, Type)]
 pub enum Scope {
     All,
     Installed,
@@ -216,10 +252,94 @@ impl From<&str> for Scope {
 
 // endregion: --- Scope
 
+// region:    --- PackageReason
+
+/// The reason a package is installed (the `reason` package attribute)
+/// It is also the reason of a transaction item, see [`crate::transaction::TransactionReason`]
+// -- check dnf5 source code:
+// -- https://github.com/rpm-software-management/dnf5/blob/main/libdnf5/transaction/transaction_item_reason.cpp
+///
+/// With the `serde` feature enabled, it (de)serializes as the reason string used by dnf5
+/// (`"User"`, `"Weak Dependency"`, ...), `Unknown` keeps the original string.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(from = "String", into = "String"))]
+pub enum PackageReason {
+    None,
+    Dependency,
+    User,
+    Clean,
+    WeakDependency,
+    Group,
+    External,
+    Unknown(String),
+}
+
+impl PackageReason {
+    /// Packages installed for this reason can be removed, when nothing needs them any more
+    pub fn is_dependency(&self) -> bool {
+        matches!(
+            self,
+            PackageReason::Dependency | PackageReason::WeakDependency | PackageReason::Clean
+        )
+    }
+}
+
+impl From<&str> for PackageReason {
+    /// PackageReason from string
+    fn from(reason: &str) -> Self {
+        match reason.to_lowercase().as_str() {
+            "none" => PackageReason::None,
+            "dependency" => PackageReason::Dependency,
+            "user" => PackageReason::User,
+            "clean" => PackageReason::Clean,
+            "weak dependency" => PackageReason::WeakDependency,
+            "group" => PackageReason::Group,
+            "external user" => PackageReason::External,
+            _ => PackageReason::Unknown(reason.to_string()),
+        }
+    }
+}
+
+impl From<String> for PackageReason {
+    fn from(reason: String) -> Self {
+        PackageReason::from(reason.as_str())
+    }
+}
+
+impl From<PackageReason> for String {
+    fn from(reason: PackageReason) -> Self {
+        reason.to_string()
+    }
+}
+
+impl core::fmt::Display for PackageReason {
+    /// String representation, as used by dnf5
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            PackageReason::None => "None",
+            PackageReason::Dependency => "Dependency",
+            PackageReason::User => "User",
+            PackageReason::Clean => "Clean",
+            PackageReason::WeakDependency => "Weak Dependency",
+            PackageReason::Group => "Group",
+            PackageReason::External => "External User",
+            PackageReason::Unknown(reason) => reason,
+        };
+        write!(f, "{s}")
+    }
+}
+
+// endregion: --- PackageReason
+
 // region:    --- DnfPackage
 /// a native rust struct to represent a dnf package
 /// it is designed to be used with get_packages
-#[derive(Debug)]
+///
+/// With the `serde` feature enabled, it (de)serializes as a flat map using the field names:
+/// `name`, `arch`, `evr`, `repo_id`, `is_installed` and `size`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct DnfPackage {
     pub name: String,
     pub arch: String,
@@ -240,7 +360,7 @@ impl DnfPackage {
     /// build a native DnfPackage from a HashMap contains values returned from call to Rpm.list() method
     /// with the following attrs: `name, arch, evr, repo_id, is_installed, installed_size`
     /// it is designed to be used with get_packages
-    pub fn from(pkg: &HashMap<String, OwnedValue>) -> Result<DnfPackage, Error> {
+    pub fn from(pkg: &HashMap<String, OwnedValue>) -> crate::Result<DnfPackage> {
         Ok(Self {
             name: from_variant!(pkg, String, "name")?,
             arch: from_variant!(pkg, String, "arch")?,
@@ -254,10 +374,82 @@ impl DnfPackage {
 
 // endregion: --- DnfPackage
 
+// region:    --- PackageRecord
+/// a native rust struct with the full name, epoch, version, release and arch of a package
+/// and the source rpm it was build from.
+/// it is used by the higher level query helpers, that need more details than [`DnfPackage`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct PackageRecord {
+    pub name: String,
+    pub epoch: String,
+    pub version: String,
+    pub release: String,
+    pub arch: String,
+    pub repo_id: String,
+    pub is_installed: bool,
+    /// source rpm filename, None if the package has no source rpm (Ex. gpg-pubkey)
+    pub sourcerpm: Option<String>,
+}
+
+impl AsRef<PackageRecord> for PackageRecord {
+    fn as_ref(&self) -> &PackageRecord {
+        self
+    }
+}
+
+impl PackageRecord {
+    /// attributes that must be requested from Rpm.list() to build a PackageRecord
+    pub fn attrs() -> Vec<PackageAttr> {
+        vec![
+            PackageAttr::Name,
+            PackageAttr::Epoch,
+            PackageAttr::Version,
+            PackageAttr::Release,
+            PackageAttr::Arch,
+            PackageAttr::RepoId,
+            PackageAttr::IsInstalled,
+            PackageAttr::Sourcerpm,
+        ]
+    }
+
+    /// build a PackageRecord from a HashMap contains values returned from call to Rpm.list() method
+    /// with the attributes returned by [`PackageRecord::attrs`]
+    pub fn from(pkg: &HashMap<String, OwnedValue>) -> crate::Result<PackageRecord> {
+        let sourcerpm = from_variant!(pkg, String, "sourcerpm")?;
+        Ok(Self {
+            name: from_variant!(pkg, String, "name")?,
+            epoch: from_variant!(pkg, String, "epoch")?,
+            version: from_variant!(pkg, String, "version")?,
+            release: from_variant!(pkg, String, "release")?,
+            arch: from_variant!(pkg, String, "arch")?,
+            repo_id: from_variant!(pkg, String, "repo_id")?,
+            is_installed: from_variant!(pkg, bool, "is_installed")?,
+            sourcerpm: if sourcerpm.is_empty() { None } else { Some(sourcerpm) },
+        })
+    }
+
+    /// epoch:version-release, the epoch is left out if it is 0 (same format as dnf5 uses)
+    pub fn evr(&self) -> String {
+        if self.epoch.is_empty() || self.epoch == "0" {
+            format!("{}-{}", self.version, self.release)
+        } else {
+            format!("{}:{}-{}", self.epoch, self.version, self.release)
+        }
+    }
+
+    /// name-epoch:version-release.arch
+    pub fn nevra(&self) -> String {
+        format!("{}-{}.{}", self.name, self.evr(), self.arch)
+    }
+}
+
+// endregion: --- PackageRecord
+
 // region:    --- ListOptions
 
 /// Stucture with options for org.rpm.dnf.v0.rpm.Rpm.list(a(sv) options.)
-#[derive(Debug, Type, Deserialize, Serialize)]
+/// It implements `Hash` and `Eq`, so it can be used as key for the query cache
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Type, Deserialize, Serialize)]
 pub struct ListOptions {
     package_attrs: Vec<PackageAttr>,
     patterns: Vec<String>,
@@ -361,6 +553,18 @@ impl ListOptionsBuilder {
         self
     }
 
+    /// Match the patterns against the provides of the packages too
+    pub fn with_provides(mut self, with_provides: bool) -> ListOptionsBuilder {
+        self.with_provides = with_provides;
+        self
+    }
+
+    /// Match the patterns against the files of the packages too
+    pub fn with_filenames(mut self, with_filenames: bool) -> ListOptionsBuilder {
+        self.with_filenames = with_filenames;
+        self
+    }
+
     /// build the ListOption object from the applied options
     pub fn build(self) -> ListOptions {
         ListOptions {
@@ -384,7 +588,7 @@ pub async fn get_packages(
     daemon: impl AsRef<DnfDaemon>,
     patterns: impl AsRef<Vec<String>>,
     scope: Scope,
-) -> Result<Vec<DnfPackage>, Error> {
+) -> crate::Result<Vec<DnfPackage>> {
     // Setup query options for use with org.rpm.dnf.v0.rpm.Rpm.list()
     // check here for details
     // https://dnf5.readthedocs.io/en/latest/dnf_daemon/dnf5daemon_dbus_api.8.html#org.rpm.dnf.v0.rpm.Rpm.list
@@ -404,19 +608,50 @@ pub async fn get_packages(
         .build();
     // println!("{:?}", options.to_dbus());
 
+    // Use the cached result, if the query cache is enabled and contains the query
+    let daemon = daemon.as_ref();
+    if let Some(packages) = daemon.cached_packages(&options) {
+        return Ok(packages);
+    }
+
     // Read packages from Rpm.list() and convert into DnfPackages
-    let pkgs = daemon
-        .as_ref()
-        .rpm
-        .list(options.to_dbus())
-        .await
-        .expect("org.rpm.dnf.v0.Rpm.list failed");
+    let pkgs = daemon.rpm.list(options.to_dbus()).await.context(RPM, "list")?;
     // println!("Raw packages: {:?}", pkgs);
-    build_packages(&pkgs)
+    let packages = build_packages(&pkgs)?;
+    daemon.cache_packages(options, &packages);
+    Ok(packages)
+}
+
+/// Get packages as [`PackageRecord`] by calling org.rpm.dnf.v0.rpm.Rpm.list()
+pub async fn get_package_records(
+    daemon: impl AsRef<DnfDaemon>,
+    patterns: impl AsRef<Vec<String>>,
+    scope: Scope,
+) -> crate::Result<Vec<PackageRecord>> {
+    let options = ListOptions::builder()
+        .attrs(PackageRecord::attrs())
+        .patterns(patterns.as_ref())
+        .scope(scope)
+        .build();
+    list_package_records(daemon, &options).await
+}
+
+/// Get packages as [`PackageRecord`] by calling org.rpm.dnf.v0.rpm.Rpm.list() with the given options.
+/// the options must contain the attributes returned by [`PackageRecord::attrs`]
+pub async fn list_package_records(
+    daemon: impl AsRef<DnfDaemon>,
+    options: &ListOptions,
+) -> crate::Result<Vec<PackageRecord>> {
+    let pkgs = daemon.as_ref().rpm.list(options.to_dbus()).await.context(RPM, "list")?;
+    let mut records = Vec::new();
+    for pkg in &pkgs.items {
+        records.push(PackageRecord::from(pkg)?);
+    }
+    Ok(records)
 }
 
 /// Convert the package HashMap's returnend by zbus to DnfPackage objects
-fn build_packages(pkgs: &ListResults) -> Result<Vec<DnfPackage>, Error> {
+fn build_packages(pkgs: &ListResults) -> crate::Result<Vec<DnfPackage>> {
     let mut packages = Vec::new();
     for pkg in &pkgs.items {
         packages.push(DnfPackage::from(pkg)?);
@@ -472,6 +707,9 @@ mod tests {
             .build();
         let pa2: Vec<String> = opts2.package_attrs.iter().map(|a| a.to_string()).collect();
         assert_eq!(pa2, vec!["name".to_string(), "evr".to_string()]);
+
+        let opts3 = ListOptions::builder().with_provides(true).build();
+        assert!(opts3.with_provides);
     }
 
     #[test]
@@ -510,6 +748,31 @@ mod tests {
         assert!(nevra.contains("true"));
     }
 
+    #[test]
+    fn listoptions_hash_and_eq() {
+        use std::collections::HashSet;
+
+        let patterns = vec!["foo*".to_string()];
+        let opts = ListOptions::builder()
+            .attrs(vec![PackageAttr::Name])
+            .patterns(&patterns)
+            .scope(Scope::Installed)
+            .build();
+        let same = opts.clone();
+        let other = ListOptions::builder()
+            .attrs(vec![PackageAttr::Name])
+            .patterns(&patterns)
+            .scope(Scope::Available)
+            .build();
+        assert_eq!(opts, same);
+        assert_ne!(opts, other);
+
+        let mut set = HashSet::new();
+        set.insert(opts);
+        assert!(set.contains(&same));
+        assert!(!set.contains(&other));
+    }
+
     #[test]
     fn package_attr_from_string() {
         assert_eq!(PackageAttr::from("name".to_string()), PackageAttr::Name);
@@ -581,7 +844,127 @@ mod tests {
         pkg.insert("name".to_string(), Value::new("testpkg").try_into_owned().unwrap());
 
         let result = DnfPackage::from(&pkg);
-        assert!(result.is_err());
+        assert!(matches!(
+            result,
+            Err(crate::Error::MissingField { field, method }) if field == "arch" && method == "Rpm.list()"
+        ));
+
+        // Field with wrong type
+        let mut pkg = HashMap::new();
+        pkg.insert("name".to_string(), Value::new(42u32).try_into_owned().unwrap());
+        let result = DnfPackage::from(&pkg);
+        assert!(matches!(
+            result,
+            Err(crate::Error::InvalidFieldType { field, .. }) if field == "name"
+        ));
+    }
+
+    #[test]
+    fn build_packages_from_malformed_reply() {
+        let mut good = HashMap::new();
+        good.insert("name".to_string(), Value::new("testpkg").try_into_owned().unwrap());
+        good.insert("arch".to_string(), Value::new("x86_64").try_into_owned().unwrap());
+        good.insert("evr".to_string(), Value::new("1.0-1").try_into_owned().unwrap());
+        good.insert("repo_id".to_string(), Value::new("fedora").try_into_owned().unwrap());
+        good.insert("is_installed".to_string(), Value::new(true).try_into_owned().unwrap());
+        good.insert(
+            "install_size".to_string(),
+            Value::new(1024u64).try_into_owned().unwrap(),
+        );
+        let mut bad = good.clone();
+        bad.insert("is_installed".to_string(), Value::new("yes").try_into_owned().unwrap());
+
+        let reply = ListResults {
+            items: vec![good.clone()],
+        };
+        assert_eq!(build_packages(&reply).unwrap().len(), 1);
+        let reply = ListResults { items: vec![good, bad] };
+        assert!(matches!(
+            build_packages(&reply),
+            Err(crate::Error::InvalidFieldType { field, .. }) if field == "is_installed"
+        ));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn dnf_package_serde_round_trip() {
+        let pkg = DnfPackage {
+            name: "testpkg".to_string(),
+            arch: "x86_64".to_string(),
+            evr: "1.0-1".to_string(),
+            repo_id: "fedora".to_string(),
+            is_installed: true,
+            size: 1024,
+        };
+        let json = serde_json::to_value(&pkg).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "name": "testpkg",
+                "arch": "x86_64",
+                "evr": "1.0-1",
+                "repo_id": "fedora",
+                "is_installed": true,
+                "size": 1024
+            })
+        );
+        let back: DnfPackage = serde_json::from_value(json).unwrap();
+        assert_eq!(back, pkg);
+    }
+
+    #[test]
+    fn package_reason_from_str_and_display() {
+        let reasons = [
+            ("None", PackageReason::None),
+            ("Dependency", PackageReason::Dependency),
+            ("User", PackageReason::User),
+            ("Clean", PackageReason::Clean),
+            ("Weak Dependency", PackageReason::WeakDependency),
+            ("Group", PackageReason::Group),
+            ("External User", PackageReason::External),
+        ];
+        for (s, reason) in reasons {
+            assert_eq!(PackageReason::from(s), reason);
+            assert_eq!(reason.to_string(), s);
+        }
+        assert_eq!(PackageReason::from("user"), PackageReason::User);
+        assert_eq!(PackageReason::from("foo"), PackageReason::Unknown("foo".to_string()));
+        assert!(PackageReason::WeakDependency.is_dependency());
+        assert!(!PackageReason::User.is_dependency());
+    }
+
+    fn record_data(epoch: &str, sourcerpm: &str) -> HashMap<String, OwnedValue> {
+        let mut pkg = HashMap::new();
+        pkg.insert("name".to_string(), Value::new("openssl-libs").try_into_owned().unwrap());
+        pkg.insert("epoch".to_string(), Value::new(epoch).try_into_owned().unwrap());
+        pkg.insert("version".to_string(), Value::new("3.0.7").try_into_owned().unwrap());
+        pkg.insert("release".to_string(), Value::new("2.fc38").try_into_owned().unwrap());
+        pkg.insert("arch".to_string(), Value::new("x86_64").try_into_owned().unwrap());
+        pkg.insert("repo_id".to_string(), Value::new("@System").try_into_owned().unwrap());
+        pkg.insert("is_installed".to_string(), Value::new(true).try_into_owned().unwrap());
+        pkg.insert("sourcerpm".to_string(), Value::new(sourcerpm).try_into_owned().unwrap());
+        pkg
+    }
+
+    #[test]
+    fn package_record_from_valid_data() {
+        let record = PackageRecord::from(&record_data("1", "openssl-3.0.7-2.fc38.src.rpm")).unwrap();
+        assert_eq!(record.name, "openssl-libs");
+        assert_eq!(record.evr(), "1:3.0.7-2.fc38");
+        assert_eq!(record.nevra(), "openssl-libs-1:3.0.7-2.fc38.x86_64");
+        assert_eq!(record.sourcerpm, Some("openssl-3.0.7-2.fc38.src.rpm".to_string()));
+
+        // no epoch and no source rpm
+        let record = PackageRecord::from(&record_data("0", "")).unwrap();
+        assert_eq!(record.evr(), "3.0.7-2.fc38");
+        assert_eq!(record.sourcerpm, None);
+    }
+
+    #[test]
+    fn package_record_from_invalid_data() {
+        let mut pkg = record_data("0", "");
+        pkg.remove("release");
+        assert!(PackageRecord::from(&pkg).is_err());
     }
 
     struct TestStruct {
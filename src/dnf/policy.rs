@@ -0,0 +1,320 @@
+use crate::transaction::{TransactionAction, TransactionMember, TransactionResult, replacements};
+use std::collections::HashSet;
+
+// region:    --- PolicyViolation
+
+/// A resolved transaction refused by a [`TransactionPolicy`], with the members that broke the policy
+#[derive(Debug, Clone, PartialEq)]
+pub struct PolicyViolation {
+    /// name of the policy
+    pub policy: String,
+    pub reason: String,
+    pub members: Vec<TransactionMember>,
+}
+
+impl PolicyViolation {
+    /// New PolicyViolation for the given policy, reason and offending members
+    pub fn new(policy: &str, reason: &str, members: Vec<TransactionMember>) -> Self {
+        Self {
+            policy: policy.to_string(),
+            reason: reason.to_string(),
+            members,
+        }
+    }
+}
+
+impl core::fmt::Display for PolicyViolation {
+    /// policy: reason (nevra, nevra, ...)
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let nevras: Vec<&str> = self.members.iter().map(|mbr| mbr.nevra.as_str()).collect();
+        write!(f, "{}: {} ({})", self.policy, self.reason, nevras.join(", "))
+    }
+}
+
+// endregion: --- PolicyViolation
+
+// region:    --- TransactionPolicy
+
+/// A check of a resolved transaction, run before it is executed.
+/// The transaction is not executed if a policy returns a violation.
+///
+/// It is implemented for closures, so a `|result: &TransactionResult| -> Option<PolicyViolation>`
+/// can be used as policy.
+pub trait TransactionPolicy: Send + Sync {
+    /// Return a violation, if the transaction breaks the policy
+    fn check(&self, result: &TransactionResult) -> Option<PolicyViolation>;
+}
+
+impl<F> TransactionPolicy for F
+where
+    F: Fn(&TransactionResult) -> Option<PolicyViolation> + Send + Sync,
+{
+    fn check(&self, result: &TransactionResult) -> Option<PolicyViolation> {
+        self(result)
+    }
+}
+
+/// Run the policies on a resolved transaction, and return all the violations
+pub fn check_policies(policies: &[Box<dyn TransactionPolicy>], result: &TransactionResult) -> Vec<PolicyViolation> {
+    policies.iter().filter_map(|policy| policy.check(result)).collect()
+}
+
+/// The packages removed from the system, removed packages and replaced packages that are not replaced
+/// by a package with the same name (Ex. obsoleted packages)
+fn removed_members(result: &TransactionResult) -> Vec<&TransactionMember> {
+    // nevras of the replaced packages, that are replaced by a newer/older version
+    let mut kept: HashSet<&str> = HashSet::new();
+    for replacement in replacements(&result.tx_members) {
+        let (name, _, _) = replacement.member.name_evr_arch();
+        for old in replacement.replaced {
+            if old.name_evr_arch().0 == name {
+                kept.insert(old.nevra.as_str());
+            }
+        }
+    }
+    result
+        .tx_members
+        .iter()
+        .filter(|mbr| match mbr.action {
+            TransactionAction::Remove => true,
+            TransactionAction::Replaced => !kept.contains(mbr.nevra.as_str()),
+            _ => false,
+        })
+        .collect()
+}
+
+/// Violation with the members, or None if there are no members
+fn violation(policy: &str, reason: &str, members: Vec<&TransactionMember>) -> Option<PolicyViolation> {
+    if members.is_empty() {
+        return None;
+    }
+    Some(PolicyViolation::new(
+        policy,
+        reason,
+        members.into_iter().cloned().collect(),
+    ))
+}
+
+// endregion: --- TransactionPolicy
+
+// region:    --- Built-in policies
+
+/// Refuse transactions that remove one of the protected packages (by name)
+#[derive(Debug, Clone, Default)]
+pub struct ProtectedPackages {
+    names: HashSet<String>,
+}
+
+impl ProtectedPackages {
+    /// New policy protecting the packages with the given names
+    pub fn new(names: &[&str]) -> Self {
+        Self {
+            names: names.iter().map(|name| name.to_string()).collect(),
+        }
+    }
+}
+
+impl TransactionPolicy for ProtectedPackages {
+    fn check(&self, result: &TransactionResult) -> Option<PolicyViolation> {
+        let members = removed_members(result)
+            .into_iter()
+            .filter(|mbr| self.names.contains(&mbr.name_evr_arch().0))
+            .collect();
+        violation("protected-packages", "protected packages would be removed", members)
+    }
+}
+
+/// Refuse transactions that remove more than a given number of packages
+#[derive(Debug, Clone, Copy)]
+pub struct MaxRemovals {
+    max: usize,
+}
+
+impl MaxRemovals {
+    /// New policy allowing at most `max` packages to be removed
+    pub fn new(max: usize) -> Self {
+        Self { max }
+    }
+}
+
+impl TransactionPolicy for MaxRemovals {
+    fn check(&self, result: &TransactionResult) -> Option<PolicyViolation> {
+        let removed = removed_members(result);
+        if removed.len() <= self.max {
+            return None;
+        }
+        let reason = format!(
+            "{} packages would be removed, at most {} allowed",
+            removed.len(),
+            self.max
+        );
+        violation("max-removals", &reason, removed)
+    }
+}
+
+/// Refuse transactions that replace a package with a package from another vendor
+/// Replacements where the vendor of one of the packages is unknown are refused too.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoVendorChange;
+
+impl TransactionPolicy for NoVendorChange {
+    fn check(&self, result: &TransactionResult) -> Option<PolicyViolation> {
+        let mut changed = Vec::new();
+        let mut unknown = Vec::new();
+        for replacement in replacements(&result.tx_members) {
+            let vendor = replacement.member.vendor.as_ref();
+            if vendor.is_none() || replacement.replaced.iter().any(|old| old.vendor.is_none()) {
+                unknown.push(replacement.member);
+            } else if replacement.replaced.iter().any(|old| old.vendor.as_ref() != vendor) {
+                changed.push(replacement.member);
+            }
+        }
+        let reason = match (changed.is_empty(), unknown.is_empty()) {
+            (false, false) => "packages would change vendor, and the vendor of other packages is unknown",
+            (true, false) => "the vendor of packages is unknown",
+            _ => "packages would change vendor",
+        };
+        changed.extend(unknown);
+        violation("no-vendor-change", reason, changed)
+    }
+}
+
+/// Refuse transactions that add packages from other repositories than the allowed ones
+/// Packages without a known repository are refused.
+#[derive(Debug, Clone, Default)]
+pub struct AllowedRepos {
+    repo_ids: HashSet<String>,
+}
+
+impl AllowedRepos {
+    /// New policy allowing packages from the given repository ids
+    pub fn new(repo_ids: &[&str]) -> Self {
+        Self {
+            repo_ids: repo_ids.iter().map(|id| id.to_string()).collect(),
+        }
+    }
+}
+
+impl TransactionPolicy for AllowedRepos {
+    fn check(&self, result: &TransactionResult) -> Option<PolicyViolation> {
+        let members = result
+            .tx_members
+            .iter()
+            .filter(|mbr| {
+                matches!(
+                    mbr.action,
+                    TransactionAction::Install
+                        | TransactionAction::Upgrade
+                        | TransactionAction::Downgrade
+                        | TransactionAction::Reinstall
+                )
+            })
+            .filter(|mbr| mbr.is_package())
+            .filter(|mbr| !mbr.repo_id.as_ref().is_some_and(|id| self.repo_ids.contains(id)))
+            .collect();
+        violation(
+            "allowed-repos",
+            "packages would be installed from other repositories",
+            members,
+        )
+    }
+}
+
+// endregion: --- Built-in policies
+
+// region:    --- Unit Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transaction::TransactionReason;
+
+    fn member(action: TransactionAction, nevra: &str, repo_id: &str, vendor: &str) -> TransactionMember {
+        TransactionMember {
+            repo_id: Some(repo_id.to_string()),
+            vendor: Some(vendor.to_string()),
+            ..TransactionMember::new(action, TransactionReason::User, nevra)
+        }
+    }
+
+    fn result() -> TransactionResult {
+        use TransactionAction as Action;
+        TransactionResult {
+            tx_members: vec![
+                member(Action::Upgrade, "kernel-6.9.1-1.x86_64", "updates", "Fedora Project"),
+                member(Action::Replaced, "kernel-6.8.1-1.x86_64", "@System", "Fedora Project"),
+                member(Action::Upgrade, "ffmpeg-7.0-1.x86_64", "rpmfusion", "RPM Fusion"),
+                member(Action::Replaced, "ffmpeg-6.1-1.x86_64", "@System", "Fedora Project"),
+                member(Action::Remove, "sudo-1.9-1.x86_64", "@System", "Fedora Project"),
+                member(Action::Remove, "vim-9.1-1.x86_64", "@System", "Fedora Project"),
+            ],
+            result_code: 0,
+        }
+    }
+
+    fn nevras(violation: &PolicyViolation) -> Vec<&str> {
+        violation.members.iter().map(|mbr| mbr.nevra.as_str()).collect()
+    }
+
+    #[test]
+    fn protected_packages() {
+        // an upgrade of a protected package is not a removal
+        let violation = ProtectedPackages::new(&["kernel", "sudo"]).check(&result()).unwrap();
+        assert_eq!(nevras(&violation), vec!["sudo-1.9-1.x86_64"]);
+        assert_eq!(
+            violation.to_string(),
+            "protected-packages: protected packages would be removed (sudo-1.9-1.x86_64)"
+        );
+        assert!(ProtectedPackages::new(&["kernel"]).check(&result()).is_none());
+    }
+
+    #[test]
+    fn max_removals() {
+        assert!(MaxRemovals::new(2).check(&result()).is_none());
+        let violation = MaxRemovals::new(1).check(&result()).unwrap();
+        assert_eq!(violation.members.len(), 2);
+        assert_eq!(violation.reason, "2 packages would be removed, at most 1 allowed");
+    }
+
+    #[test]
+    fn vendor_and_repos() {
+        let violation = NoVendorChange.check(&result()).unwrap();
+        assert_eq!(nevras(&violation), vec!["ffmpeg-7.0-1.x86_64"]);
+
+        // the vendor is unknown, when it could not be looked up
+        let mut unknown = result();
+        unknown.tx_members[1].vendor = None;
+        let violation = NoVendorChange.check(&unknown).unwrap();
+        assert_eq!(
+            violation.reason,
+            "packages would change vendor, and the vendor of other packages is unknown"
+        );
+        // both the changed and the unknown packages are reported
+        assert_eq!(nevras(&violation), vec!["ffmpeg-7.0-1.x86_64", "kernel-6.9.1-1.x86_64"]);
+        unknown.tx_members[2].vendor = None;
+        let violation = NoVendorChange.check(&unknown).unwrap();
+        assert_eq!(violation.reason, "the vendor of packages is unknown");
+        assert_eq!(nevras(&violation), vec!["kernel-6.9.1-1.x86_64", "ffmpeg-7.0-1.x86_64"]);
+
+        let violation = AllowedRepos::new(&["fedora", "updates"]).check(&result()).unwrap();
+        assert_eq!(nevras(&violation), vec!["ffmpeg-7.0-1.x86_64"]);
+        assert!(AllowedRepos::new(&["updates", "rpmfusion"]).check(&result()).is_none());
+    }
+
+    #[test]
+    fn closure_policies() {
+        let policies: Vec<Box<dyn TransactionPolicy>> = vec![
+            Box::new(MaxRemovals::new(10)),
+            Box::new(|result: &TransactionResult| {
+                let members = result.members_with_action(&TransactionAction::Upgrade).collect();
+                violation("no-upgrades", "upgrades are not allowed", members)
+            }),
+        ];
+        let violations = check_policies(&policies, &result());
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].policy, "no-upgrades");
+        assert_eq!(violations[0].members.len(), 2);
+    }
+}
+
+// endregion: --- Unit Tests
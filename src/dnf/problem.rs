@@ -0,0 +1,400 @@
+use crate::Result;
+use crate::dnf::package::{from_variant, optional_variant};
+use std::collections::HashMap;
+use zbus::zvariant::{OwnedValue, Value};
+
+// region:    --- Types
+
+// -- Custom type for one problem returned by Goal.get_transaction_problems()
+type ProblemMap = HashMap<String, OwnedValue>;
+
+const METHOD: &str = "Goal.get_transaction_problems()";
+
+// endregion: --- Types
+
+// region:    --- Codes
+
+/// GoalProblem codes used in the `problem` field
+// -- check dnf5 source code:
+// -- https://github.com/rpm-software-management/dnf5/blob/3739c4a34db6e7abcd8b4faf0db7d5307f37d340/include/libdnf5/base/goal_elements.hpp
+// -- the values are from goal_elements.hpp at the commit above, check them
+// -- when newer dnf5 releases add problems
+mod goal_problem {
+    pub const SOLVER_ERROR: u32 = 1 << 0;
+    pub const NOT_FOUND: u32 = 1 << 1;
+    pub const EXCLUDED: u32 = 1 << 2;
+    pub const NOT_FOUND_IN_REPOSITORIES: u32 = 1 << 4;
+    pub const NOT_INSTALLED: u32 = 1 << 5;
+    pub const NOT_INSTALLED_FOR_ARCHITECTURE: u32 = 1 << 6;
+    pub const NOT_AVAILABLE: u32 = 1 << 11;
+    pub const ALREADY_INSTALLED: u32 = 1 << 12;
+    pub const MULTIPLE_STREAMS: u32 = 1 << 16;
+    pub const EXCLUDED_VERSIONLOCK: u32 = 1 << 17;
+    pub const MALFORMED: u32 = 1 << 18;
+}
+
+/// ProblemRules codes used in the `solver_problems` field
+// -- check dnf5 source code:
+// -- https://github.com/rpm-software-management/dnf5/blob/3739c4a34db6e7abcd8b4faf0db7d5307f37d340/include/libdnf5/base/goal_elements.hpp
+mod problem_rule {
+    pub const RULE_JOB: u32 = 4;
+    pub const RULE_JOB_NOTHING_PROVIDES_DEP: u32 = 6;
+    pub const RULE_PKG_NOTHING_PROVIDES_DEP: u32 = 16;
+    pub const RULE_PKG_CONFLICTS: u32 = 18;
+    pub const RULE_PKG_REQUIRES: u32 = 22;
+    pub const RULE_PKG_SELF_CONFLICT: u32 = 23;
+    pub const RULE_PKG_REMOVAL_OF_PROTECTED: u32 = 26;
+    pub const RULE_PKG_REMOVAL_OF_RUNNING_KERNEL: u32 = 27;
+    pub const RULE_PKG_INSTALLED_CONFLICTS: u32 = 31;
+}
+
+// endregion: --- Codes
+
+// region:    --- ProblemData
+
+/// One rule of a solver problem, the code is a libdnf5 ProblemRules value
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SolverRule {
+    pub code: u32,
+    /// the packages and dependencies the rule is about (Ex. nevra and dependency for a missing dependency)
+    pub args: Vec<String>,
+}
+
+/// The raw data of a problem returned by org.rpm.dnf.v0.Goal.get_transaction_problems()
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProblemData {
+    /// libdnf5 GoalAction value
+    pub action: u32,
+    /// libdnf5 GoalProblem value
+    pub problem: u32,
+    pub spec: Option<String>,
+    pub additional_data: Vec<String>,
+    /// the solver problems, each is a list of the rules that caused it
+    pub solver_problems: Vec<Vec<SolverRule>>,
+}
+
+impl ProblemData {
+    /// Create a ProblemData from a map returned by Goal.get_transaction_problems()
+    pub fn from(map: &ProblemMap) -> Result<Self> {
+        Ok(Self {
+            action: from_variant!(map, u32, "action", METHOD)?,
+            problem: from_variant!(map, u32, "problem", METHOD)?,
+            spec: optional_variant(map, METHOD, "spec")?,
+            additional_data: optional_variant(map, METHOD, "additional_data")?.unwrap_or_default(),
+            solver_problems: solver_problems(map)?,
+        })
+    }
+}
+
+/// Decode the solver_problems field, the rule code is send as signed or unsigned integer
+fn solver_problems(map: &ProblemMap) -> Result<Vec<Vec<SolverRule>>> {
+    let Some(value) = map.get("solver_problems") else {
+        return Ok(Vec::new());
+    };
+    let to_rules = |rules: Vec<(u32, Vec<String>)>| -> Vec<SolverRule> {
+        rules.into_iter().map(|(code, args)| SolverRule { code, args }).collect()
+    };
+    if let Ok(problems) = Vec::<Vec<(u32, Vec<String>)>>::try_from(Value::from(value.to_owned())) {
+        return Ok(problems.into_iter().map(to_rules).collect());
+    }
+    let problems = Vec::<Vec<(i32, Vec<String>)>>::try_from(Value::from(value.to_owned())).map_err(|e| {
+        crate::Error::InvalidFieldType {
+            field: "solver_problems".to_string(),
+            method: METHOD.to_string(),
+            reason: e.to_string(),
+        }
+    })?;
+    Ok(problems
+        .into_iter()
+        .map(|rules| to_rules(rules.into_iter().map(|(code, args)| (code as u32, args)).collect()))
+        .collect())
+}
+
+// endregion: --- ProblemData
+
+// region:    --- ResolveProblem
+
+/// A typed problem that prevented a transaction from being resolved.
+/// Each variant contains the spec or nevra the problem is about, and the raw problem data.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ResolveProblem {
+    /// no package matches the spec
+    NoMatch {
+        spec: String,
+        data: ProblemData,
+    },
+    /// the matching packages are excluded (Ex. by excludepkgs or versionlock)
+    Excluded {
+        spec: String,
+        data: ProblemData,
+    },
+    /// the spec must match an installed package (Ex. for remove or reinstall)
+    NotInstalled {
+        spec: String,
+        data: ProblemData,
+    },
+    AlreadyInstalled {
+        spec: String,
+        data: ProblemData,
+    },
+    /// the spec can't be parsed
+    InvalidSpec {
+        spec: String,
+        data: ProblemData,
+    },
+    /// the requests in the transaction can't be done together
+    ConflictingRequests {
+        specs: Vec<String>,
+        data: ProblemData,
+    },
+    /// packages that conflicts with each other
+    Conflict {
+        packages: Vec<String>,
+        data: ProblemData,
+    },
+    /// a dependency that can't be satisfied
+    BrokenDependency {
+        package: String,
+        dependency: String,
+        data: ProblemData,
+    },
+    /// the transaction would remove a protected package (or the running kernel)
+    ProtectedRemoval {
+        package: String,
+        data: ProblemData,
+    },
+    /// other problems found by the solver
+    Solver {
+        rules: Vec<SolverRule>,
+        data: ProblemData,
+    },
+    /// other problems
+    Other {
+        spec: Option<String>,
+        data: ProblemData,
+    },
+}
+
+impl ResolveProblem {
+    /// Decode the problems returned by Goal.get_transaction_problems()
+    pub fn from_problems(problems: &[ProblemMap]) -> Result<Vec<ResolveProblem>> {
+        let mut result = Vec::new();
+        for map in problems {
+            result.extend(Self::from_data(ProblemData::from(map)?));
+        }
+        Ok(result)
+    }
+
+    /// Make the typed problems from the raw data, a solver error can give more than one problem
+    pub fn from_data(data: ProblemData) -> Vec<ResolveProblem> {
+        use goal_problem::*;
+        let spec = data.spec.clone().unwrap_or_default();
+        let problem = match data.problem {
+            NOT_FOUND | NOT_FOUND_IN_REPOSITORIES | NOT_AVAILABLE => ResolveProblem::NoMatch { spec, data },
+            EXCLUDED | EXCLUDED_VERSIONLOCK => ResolveProblem::Excluded { spec, data },
+            NOT_INSTALLED | NOT_INSTALLED_FOR_ARCHITECTURE => ResolveProblem::NotInstalled { spec, data },
+            ALREADY_INSTALLED => ResolveProblem::AlreadyInstalled { spec, data },
+            MALFORMED => ResolveProblem::InvalidSpec { spec, data },
+            SOLVER_ERROR if !data.solver_problems.is_empty() => {
+                return data
+                    .solver_problems
+                    .iter()
+                    .map(|rules| Self::from_rules(rules, &data))
+                    .collect();
+            }
+            _ => ResolveProblem::Other {
+                spec: data.spec.clone(),
+                data,
+            },
+        };
+        vec![problem]
+    }
+
+    /// Classify a solver problem by the first rule that we know
+    fn from_rules(rules: &[SolverRule], data: &ProblemData) -> ResolveProblem {
+        use problem_rule::*;
+        let data = ProblemData {
+            solver_problems: vec![rules.to_vec()],
+            ..data.clone()
+        };
+        let arg = |rule: &SolverRule, idx: usize| rule.args.get(idx).cloned().unwrap_or_default();
+        for rule in rules {
+            match rule.code {
+                RULE_JOB => {
+                    return ResolveProblem::ConflictingRequests {
+                        specs: rule.args.clone(),
+                        data,
+                    };
+                }
+                RULE_PKG_CONFLICTS | RULE_PKG_SELF_CONFLICT | RULE_PKG_INSTALLED_CONFLICTS => {
+                    return ResolveProblem::Conflict {
+                        packages: rule.args.clone(),
+                        data,
+                    };
+                }
+                RULE_PKG_NOTHING_PROVIDES_DEP | RULE_PKG_REQUIRES => {
+                    return ResolveProblem::BrokenDependency {
+                        package: arg(rule, 0),
+                        dependency: arg(rule, 1),
+                        data,
+                    };
+                }
+                RULE_JOB_NOTHING_PROVIDES_DEP => {
+                    return ResolveProblem::BrokenDependency {
+                        package: String::new(),
+                        dependency: arg(rule, 0),
+                        data,
+                    };
+                }
+                RULE_PKG_REMOVAL_OF_PROTECTED | RULE_PKG_REMOVAL_OF_RUNNING_KERNEL => {
+                    return ResolveProblem::ProtectedRemoval {
+                        package: arg(rule, 0),
+                        data,
+                    };
+                }
+                _ => {}
+            }
+        }
+        ResolveProblem::Solver {
+            rules: rules.to_vec(),
+            data,
+        }
+    }
+
+    /// The spec from the request, the problem is about (if any)
+    pub fn spec(&self) -> Option<&str> {
+        self.data().spec.as_deref()
+    }
+
+    /// The raw problem data
+    pub fn data(&self) -> &ProblemData {
+        match self {
+            ResolveProblem::NoMatch { data, .. }
+            | ResolveProblem::Excluded { data, .. }
+            | ResolveProblem::NotInstalled { data, .. }
+            | ResolveProblem::AlreadyInstalled { data, .. }
+            | ResolveProblem::InvalidSpec { data, .. }
+            | ResolveProblem::ConflictingRequests { data, .. }
+            | ResolveProblem::Conflict { data, .. }
+            | ResolveProblem::BrokenDependency { data, .. }
+            | ResolveProblem::ProtectedRemoval { data, .. }
+            | ResolveProblem::Solver { data, .. }
+            | ResolveProblem::Other { data, .. } => data,
+        }
+    }
+}
+
+// endregion: --- ResolveProblem
+
+// region:    --- Unit Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn problem_map(problem: u32, spec: Option<&str>) -> ProblemMap {
+        let mut map = HashMap::new();
+        map.insert("action".to_string(), Value::new(1u32).try_into_owned().unwrap());
+        map.insert("problem".to_string(), Value::new(problem).try_into_owned().unwrap());
+        if let Some(spec) = spec {
+            map.insert("spec".to_string(), Value::new(spec).try_into_owned().unwrap());
+        }
+        map
+    }
+
+    fn with_solver_problems(mut map: ProblemMap, problems: Vec<Vec<(u32, Vec<String>)>>) -> ProblemMap {
+        map.insert(
+            "solver_problems".to_string(),
+            Value::new(problems).try_into_owned().unwrap(),
+        );
+        map
+    }
+
+    #[test]
+    fn spec_problems() {
+        // literal GoalProblem codes, so a wrong constant is caught
+        let maps = vec![
+            problem_map(2, Some("nonexistent-package")),
+            problem_map(32, Some("0xFFFF")),
+            problem_map(4096, Some("dnf5")),
+            problem_map(262144, Some("foo>=")),
+            problem_map(131072, Some("kernel")),
+            problem_map(4, Some("bar")),
+            problem_map(16, Some("baz")),
+            problem_map(2048, Some("qux")),
+            problem_map(64, Some("quux.i686")),
+            // INSTALLED_IN_DIFFERENT_VERSION
+            problem_map(1024, Some("corge")),
+            // MULTIPLE_STREAMS
+            problem_map(65536, Some("nodejs")),
+        ];
+        let problems = ResolveProblem::from_problems(&maps).unwrap();
+        assert_eq!(problems.len(), 11);
+        assert!(matches!(&problems[0], ResolveProblem::NoMatch { spec, .. } if spec == "nonexistent-package"));
+        assert!(matches!(&problems[1], ResolveProblem::NotInstalled { spec, .. } if spec == "0xFFFF"));
+        assert!(matches!(&problems[2], ResolveProblem::AlreadyInstalled { spec, .. } if spec == "dnf5"));
+        assert!(matches!(&problems[3], ResolveProblem::InvalidSpec { .. }));
+        assert!(matches!(&problems[4], ResolveProblem::Excluded { .. }));
+        assert!(matches!(&problems[5], ResolveProblem::Excluded { .. }));
+        assert!(matches!(&problems[6], ResolveProblem::NoMatch { .. }));
+        assert!(matches!(&problems[7], ResolveProblem::NoMatch { .. }));
+        assert!(matches!(&problems[8], ResolveProblem::NotInstalled { .. }));
+        assert!(matches!(&problems[9], ResolveProblem::Other { spec: Some(_), .. }));
+        assert!(matches!(&problems[10], ResolveProblem::Other { spec: Some(spec), .. } if spec == "nodejs"));
+        assert_eq!(problems[0].spec(), Some("nonexistent-package"));
+        assert_eq!(problems[0].data().action, 1);
+        assert!(matches!(
+            ResolveProblem::from_problems(&[problem_map(1 << 13, None)]).unwrap()[0],
+            ResolveProblem::Other { spec: None, .. }
+        ));
+    }
+
+    #[test]
+    fn solver_problems_are_classified() {
+        let args = |args: &[&str]| args.iter().map(|a| a.to_string()).collect::<Vec<String>>();
+        let map = with_solver_problems(
+            problem_map(1, None),
+            vec![
+                vec![(16, args(&["foo-1.0-1.x86_64", "libbar.so.1()(64bit)"]))],
+                vec![(18, args(&["foo-1.0-1.x86_64", "bar", "bar-2.0-1.x86_64"]))],
+                vec![(26, args(&["dnf5-5.2.0-1.x86_64"]))],
+                vec![(4, args(&["foo", "bar"]))],
+                vec![(99, args(&["something"]))],
+            ],
+        );
+        let problems = ResolveProblem::from_problems(&[map]).unwrap();
+        assert_eq!(problems.len(), 5);
+        assert!(matches!(
+            &problems[0],
+            ResolveProblem::BrokenDependency { package, dependency, .. }
+                if package == "foo-1.0-1.x86_64" && dependency == "libbar.so.1()(64bit)"
+        ));
+        assert!(matches!(&problems[1], ResolveProblem::Conflict { packages, .. } if packages.len() == 3));
+        assert!(matches!(
+            &problems[2],
+            ResolveProblem::ProtectedRemoval { package, .. } if package == "dnf5-5.2.0-1.x86_64"
+        ));
+        assert!(matches!(&problems[3], ResolveProblem::ConflictingRequests { specs, .. } if specs.len() == 2));
+        assert!(matches!(&problems[4], ResolveProblem::Solver { rules, .. } if rules[0].code == 99));
+        // each problem only keeps its own solver problem
+        assert_eq!(problems[1].data().solver_problems.len(), 1);
+    }
+
+    #[test]
+    fn malformed_problems() {
+        let mut map = problem_map(goal_problem::NOT_FOUND, None);
+        map.remove("action");
+        assert!(matches!(
+            ProblemData::from(&map),
+            Err(crate::Error::MissingField { field, .. }) if field == "action"
+        ));
+        let mut map = problem_map(goal_problem::NOT_FOUND, None);
+        map.insert("spec".to_string(), Value::new(1u32).try_into_owned().unwrap());
+        assert!(matches!(
+            ProblemData::from(&map),
+            Err(crate::Error::InvalidFieldType { field, .. }) if field == "spec"
+        ));
+    }
+}
+
+// endregion: --- Unit Tests
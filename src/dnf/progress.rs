@@ -0,0 +1,552 @@
+use crate::DnfDaemon;
+use crate::transaction::{TransactionAction, TransactionOutcome, TransactionResult};
+use crate::{Error, Result};
+use futures::Stream;
+use futures::future;
+use futures::stream::BoxStream;
+use futures_util::StreamExt;
+use std::time::Instant;
+use zbus::message::{self, Message};
+use zbus::zvariant::ObjectPath;
+use zbus::{MatchRule, MessageStream};
+
+// region:    --- macroes
+
+/// Macro to subscribe to a signal on a proxy, keeping only the signals for the given session
+/// and mapping the signal arguments into an event
+/// signal_stream!(proxy, receive_<signal>, session_path, |args| event)
+macro_rules! signal_stream {
+    ($proxy:expr, $receive:ident, $session:expr, |$args:ident| $event:expr) => {{
+        let session = $session.to_string();
+        $proxy
+            .$receive()
+            .await?
+            .filter_map(move |signal| {
+                let session = session.clone();
+                async move {
+                    let $args = signal.args().ok()?;
+                    if $args.session_object_path.as_str() != session {
+                        return None;
+                    }
+                    Some($event)
+                }
+            })
+            .boxed()
+    }};
+}
+
+pub(crate) use signal_stream;
+
+/// Macro to decode the arguments of a signal message of a session into an event, returning None from
+/// the calling function, if the arguments don't match or the signal is for another session
+/// signal_event!(message, session_path, (arg: Type, ...) => event)
+macro_rules! signal_event {
+    ($msg:expr, $session:expr, ($($arg:ident: $ty:ty),*) => $event:expr) => {{
+        let body = $msg.body();
+        let (session_object_path, $($arg),*): (zbus::zvariant::ObjectPath<'_>, $($ty),*) = body.deserialize().ok()?;
+        if session_object_path.as_str() != $session {
+            return None;
+        }
+        Some($event)
+    }};
+}
+
+pub(crate) use signal_event;
+
+// endregion: --- macroes
+
+// region:    --- Enums
+
+/// Type of a rpm scriptlet, as reported in the transaction_script_* signals
+// -- check dnf5 source code:
+// -- https://github.com/rpm-software-management/dnf5/blob/main/include/libdnf5/rpm/transaction_callbacks.hpp
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScriptletType {
+    PreInstall,
+    PostInstall,
+    PreUninstall,
+    PostUninstall,
+    PreTransaction,
+    PostTransaction,
+    TriggerPreInstall,
+    TriggerInstall,
+    TriggerUninstall,
+    TriggerPostUninstall,
+    Sysusers,
+    Unknown(u32),
+}
+
+impl From<u32> for ScriptletType {
+    fn from(code: u32) -> Self {
+        match code {
+            1 => ScriptletType::PreInstall,
+            2 => ScriptletType::PostInstall,
+            3 => ScriptletType::PreUninstall,
+            4 => ScriptletType::PostUninstall,
+            5 => ScriptletType::PreTransaction,
+            6 => ScriptletType::PostTransaction,
+            7 => ScriptletType::TriggerPreInstall,
+            8 => ScriptletType::TriggerInstall,
+            9 => ScriptletType::TriggerUninstall,
+            10 => ScriptletType::TriggerPostUninstall,
+            11 => ScriptletType::Sysusers,
+            _ => ScriptletType::Unknown(code),
+        }
+    }
+}
+
+impl core::fmt::Display for ScriptletType {
+    /// the scriptlet name used in rpm spec files (Ex. %post)
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            ScriptletType::PreInstall => "%pre",
+            ScriptletType::PostInstall => "%post",
+            ScriptletType::PreUninstall => "%preun",
+            ScriptletType::PostUninstall => "%postun",
+            ScriptletType::PreTransaction => "%pretrans",
+            ScriptletType::PostTransaction => "%posttrans",
+            ScriptletType::TriggerPreInstall => "%triggerprein",
+            ScriptletType::TriggerInstall => "%triggerin",
+            ScriptletType::TriggerUninstall => "%triggerun",
+            ScriptletType::TriggerPostUninstall => "%triggerpostun",
+            ScriptletType::Sysusers => "%sysusers",
+            ScriptletType::Unknown(code) => return write!(f, "unknown scriptlet ({code})"),
+        };
+        write!(f, "{s}")
+    }
+}
+
+/// Progress of a running transaction, made from the transaction_* signals of org.rpm.dnf.v0.rpm.Rpm
+///
+/// The last event in the stream is always `Finished`, with the result of the transaction
+#[derive(Debug)]
+pub enum TransactionEvent {
+    /// the transaction is about to start, with the total number of packages
+    BeforeBegin {
+        total: u64,
+    },
+    VerifyStart {
+        total: u64,
+    },
+    VerifyProgress {
+        processed: u64,
+        total: u64,
+    },
+    VerifyStop {
+        total: u64,
+    },
+    /// rpm transaction preparation
+    TransactionStart {
+        total: u64,
+    },
+    TransactionProgress {
+        processed: u64,
+        total: u64,
+    },
+    TransactionStop {
+        total: u64,
+    },
+    /// an action on a package is started
+    ActionStart {
+        nevra: String,
+        action: TransactionAction,
+        total: u64,
+    },
+    ActionProgress {
+        nevra: String,
+        processed: u64,
+        total: u64,
+    },
+    ActionStop {
+        nevra: String,
+        total: u64,
+    },
+    /// the package is processed as element of the transaction
+    ElemProgress {
+        nevra: String,
+        processed: u64,
+        total: u64,
+    },
+    ScriptStart {
+        nevra: String,
+        scriptlet: ScriptletType,
+    },
+    ScriptStop {
+        nevra: String,
+        scriptlet: ScriptletType,
+        return_code: u64,
+    },
+    ScriptError {
+        nevra: String,
+        scriptlet: ScriptletType,
+        return_code: u64,
+    },
+    UnpackError {
+        nevra: String,
+    },
+    /// rpm has completed the transaction
+    AfterComplete {
+        success: bool,
+    },
+    /// Goal.do_transaction() has returned
+    Finished(Result<TransactionOutcome>),
+}
+
+// endregion: --- Enums
+
+// region:    --- Outcome
+
+/// A scriptlet that failed while the transaction was running
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScriptError {
+    pub nevra: String,
+    pub scriptlet: ScriptletType,
+    pub return_code: u64,
+}
+
+impl core::fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} scriptlet of {} failed with return code {}",
+            self.scriptlet, self.nevra, self.return_code
+        )
+    }
+}
+
+/// Collect the events of a running transaction, that are part of the [`TransactionOutcome`]
+#[derive(Debug)]
+pub(crate) struct OutcomeRecorder {
+    started: Instant,
+    success: Option<bool>,
+    unpack_errors: Vec<String>,
+    script_errors: Vec<ScriptError>,
+}
+
+impl Default for OutcomeRecorder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OutcomeRecorder {
+    /// New recorder, the elapsed time is measured from now
+    pub(crate) fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            success: None,
+            unpack_errors: Vec::new(),
+            script_errors: Vec::new(),
+        }
+    }
+
+    /// Record an event, if it is part of the outcome
+    pub(crate) fn record(&mut self, event: &TransactionEvent) {
+        match event {
+            TransactionEvent::AfterComplete { success } => self.success = Some(*success),
+            TransactionEvent::UnpackError { nevra } => self.unpack_errors.push(nevra.clone()),
+            TransactionEvent::ScriptError {
+                nevra,
+                scriptlet,
+                return_code,
+            } => self.script_errors.push(ScriptError {
+                nevra: nevra.clone(),
+                scriptlet: *scriptlet,
+                return_code: *return_code,
+            }),
+            _ => {}
+        }
+    }
+
+    /// Make the outcome from the recorded events and the result of Goal.do_transaction()
+    /// A failed call, or a failure reported by rpm, gives a `Error::TransactionFailed` error.
+    pub(crate) fn finish(self, result: TransactionResult, rc: Result<()>) -> Result<TransactionOutcome> {
+        let outcome = TransactionOutcome {
+            result,
+            success: self.success,
+            unpack_errors: self.unpack_errors,
+            script_errors: self.script_errors,
+            elapsed: self.started.elapsed(),
+        };
+        match rc {
+            Err(e) => Err(Error::TransactionFailed {
+                message: e.to_string(),
+                outcome: Box::new(outcome),
+                source: Some(Box::new(e)),
+            }),
+            Ok(()) if !outcome.is_success() => Err(Error::TransactionFailed {
+                message: "rpm reported a failed transaction".to_string(),
+                outcome: Box::new(outcome),
+                source: None,
+            }),
+            Ok(()) => Ok(outcome),
+        }
+    }
+}
+
+// endregion: --- Outcome
+
+// region:    --- Signal streams
+
+/// Subscribe to all signals of the interface of a proxy, for the session of the daemon.
+/// A single stream keeps the signals in the order they were send, which separate streams per signal
+/// don't guarantee.
+pub(crate) async fn session_signals(proxy: &zbus::Proxy<'_>, session: &str) -> Result<MessageStream> {
+    let rule = MatchRule::builder()
+        .msg_type(message::Type::Signal)
+        .interface(proxy.interface())?
+        .arg_path(0, session)?
+        .build();
+    Ok(MessageStream::for_match_rule(rule, proxy.connection(), None).await?)
+}
+
+/// Subscribe to all transaction_* signals for the session of the daemon
+/// The stream must be made before the transaction is started, so no events are missed.
+pub(crate) async fn transaction_events(daemon: &DnfDaemon) -> Result<BoxStream<'static, TransactionEvent>> {
+    let session = daemon.session_path();
+    let messages = session_signals(daemon.rpm.inner(), session).await?;
+    Ok(decode_transaction_events(messages, session.to_string()))
+}
+
+/// Decode the signal messages into transaction events, in the order they are received
+fn decode_transaction_events(
+    messages: impl Stream<Item = zbus::Result<Message>> + Send + 'static,
+    session: String,
+) -> BoxStream<'static, TransactionEvent> {
+    messages
+        .filter_map(move |msg| future::ready(msg.ok().and_then(|msg| transaction_event(&msg, &session))))
+        .boxed()
+}
+
+/// Decode a transaction_* signal of org.rpm.dnf.v0.rpm.Rpm for the session into an event
+fn transaction_event(msg: &Message, session: &str) -> Option<TransactionEvent> {
+    let header = msg.header();
+    match header.member()?.as_str() {
+        "transaction_before_begin" => signal_event!(msg, session, (total: u64) => {
+            TransactionEvent::BeforeBegin { total }
+        }),
+        "transaction_verify_start" => signal_event!(msg, session, (total: u64) => {
+            TransactionEvent::VerifyStart { total }
+        }),
+        "transaction_verify_progress" => signal_event!(msg, session, (processed: u64, total: u64) => {
+            TransactionEvent::VerifyProgress { processed, total }
+        }),
+        "transaction_verify_stop" => signal_event!(msg, session, (total: u64) => {
+            TransactionEvent::VerifyStop { total }
+        }),
+        "transaction_transaction_start" => signal_event!(msg, session, (total: u64) => {
+            TransactionEvent::TransactionStart { total }
+        }),
+        "transaction_transaction_progress" => signal_event!(msg, session, (processed: u64, total: u64) => {
+            TransactionEvent::TransactionProgress { processed, total }
+        }),
+        "transaction_transaction_stop" => signal_event!(msg, session, (total: u64) => {
+            TransactionEvent::TransactionStop { total }
+        }),
+        "transaction_action_start" => signal_event!(msg, session, (nevra: &str, action: u32, total: u64) => {
+            TransactionEvent::ActionStart {
+                nevra: nevra.to_string(),
+                action: TransactionAction::from_code(action),
+                total,
+            }
+        }),
+        "transaction_action_progress" => signal_event!(msg, session, (nevra: &str, processed: u64, total: u64) => {
+            TransactionEvent::ActionProgress {
+                nevra: nevra.to_string(),
+                processed,
+                total,
+            }
+        }),
+        "transaction_action_stop" => signal_event!(msg, session, (nevra: &str, total: u64) => {
+            TransactionEvent::ActionStop {
+                nevra: nevra.to_string(),
+                total,
+            }
+        }),
+        "transaction_elem_progress" => signal_event!(msg, session, (nevra: &str, processed: u64, total: u64) => {
+            TransactionEvent::ElemProgress {
+                nevra: nevra.to_string(),
+                processed,
+                total,
+            }
+        }),
+        "transaction_script_start" => signal_event!(msg, session, (nevra: &str, scriptlet_type: u32) => {
+            TransactionEvent::ScriptStart {
+                nevra: nevra.to_string(),
+                scriptlet: scriptlet_type.into(),
+            }
+        }),
+        "transaction_script_stop" => {
+            signal_event!(msg, session, (nevra: &str, scriptlet_type: u32, return_code: u64) => {
+                TransactionEvent::ScriptStop {
+                    nevra: nevra.to_string(),
+                    scriptlet: scriptlet_type.into(),
+                    return_code,
+                }
+            })
+        }
+        "transaction_script_error" => {
+            signal_event!(msg, session, (nevra: &str, scriptlet_type: u32, return_code: u64) => {
+                TransactionEvent::ScriptError {
+                    nevra: nevra.to_string(),
+                    scriptlet: scriptlet_type.into(),
+                    return_code,
+                }
+            })
+        }
+        "transaction_unpack_error" => signal_event!(msg, session, (nevra: &str) => {
+            TransactionEvent::UnpackError { nevra: nevra.to_string() }
+        }),
+        "transaction_after_complete" => signal_event!(msg, session, (success: bool) => {
+            TransactionEvent::AfterComplete { success }
+        }),
+        _ => None,
+    }
+}
+
+// endregion: --- Signal streams
+
+// region:    --- Unit Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scriptlet_type_from_code() {
+        assert_eq!(ScriptletType::from(2), ScriptletType::PostInstall);
+        assert_eq!(ScriptletType::from(6), ScriptletType::PostTransaction);
+        assert_eq!(ScriptletType::from(11), ScriptletType::Sysusers);
+        assert_eq!(ScriptletType::from(0), ScriptletType::Unknown(0));
+        assert_eq!(ScriptletType::PostInstall.to_string(), "%post");
+        assert_eq!(ScriptletType::TriggerPostUninstall.to_string(), "%triggerpostun");
+        assert_eq!(ScriptletType::Unknown(42).to_string(), "unknown scriptlet (42)");
+    }
+
+    fn empty_result() -> TransactionResult {
+        TransactionResult {
+            tx_members: Vec::new(),
+            result_code: 0,
+        }
+    }
+
+    #[test]
+    fn outcome_recorder() {
+        let mut recorder = OutcomeRecorder::new();
+        recorder.record(&TransactionEvent::ScriptError {
+            nevra: "foo-1.0-1.x86_64".to_string(),
+            scriptlet: ScriptletType::PostInstall,
+            return_code: 1,
+        });
+        recorder.record(&TransactionEvent::BeforeBegin { total: 1 });
+        recorder.record(&TransactionEvent::AfterComplete { success: true });
+        let outcome = recorder.finish(empty_result(), Ok(())).unwrap();
+        assert_eq!(outcome.success, Some(true));
+        assert!(outcome.is_success());
+        assert_eq!(
+            outcome.script_errors[0].to_string(),
+            "%post scriptlet of foo-1.0-1.x86_64 failed with return code 1"
+        );
+
+        // no signals (Ex. offline transaction) is a success
+        let outcome = OutcomeRecorder::new().finish(empty_result(), Ok(())).unwrap();
+        assert_eq!(outcome.success, None);
+        assert!(outcome.is_success());
+    }
+
+    #[test]
+    fn outcome_recorder_failures() {
+        let mut recorder = OutcomeRecorder::new();
+        recorder.record(&TransactionEvent::UnpackError {
+            nevra: "bar-2.0-1.x86_64".to_string(),
+        });
+        recorder.record(&TransactionEvent::AfterComplete { success: false });
+        let err = recorder.finish(empty_result(), Ok(())).unwrap_err();
+        assert!(matches!(
+            &err,
+            Error::TransactionFailed { outcome, source: None, .. }
+                if outcome.success == Some(false) && outcome.unpack_errors == vec!["bar-2.0-1.x86_64"]
+        ));
+        assert_eq!(
+            err.to_string(),
+            "transaction failed: rpm reported a failed transaction; could not unpack bar-2.0-1.x86_64"
+        );
+
+        // a failed call keeps the error from the call
+        let rc = Err(Error::DnfDaemon("rpm transaction failed".to_string()));
+        let err = OutcomeRecorder::new().finish(empty_result(), rc).unwrap_err();
+        assert!(matches!(
+            &err,
+            Error::TransactionFailed { source: Some(source), .. } if matches!(source.as_ref(), Error::DnfDaemon(_))
+        ));
+        assert!(std::error::Error::source(&err).is_some());
+    }
+
+    const SESSION: &str = "/org/rpm/dnf/v0/session/1";
+
+    fn signal<B>(member: &str, body: &B) -> zbus::Result<Message>
+    where
+        B: serde::Serialize + zbus::zvariant::DynamicType,
+    {
+        Message::signal(SESSION, "org.rpm.dnf.v0.rpm.Rpm", member)?.build(body)
+    }
+
+    #[tokio::test]
+    async fn decode_interleaved_transaction_events() {
+        let session = ObjectPath::try_from(SESSION).unwrap();
+        let other = ObjectPath::try_from("/org/rpm/dnf/v0/session/2").unwrap();
+        let messages = vec![
+            signal("transaction_before_begin", &(&session, 2u64)),
+            signal(
+                "transaction_action_start",
+                &(&session, "foo-1.0-1.x86_64", 1u32, 100u64),
+            ),
+            signal(
+                "transaction_action_progress",
+                &(&session, "foo-1.0-1.x86_64", 50u64, 100u64),
+            ),
+            // another session and an unknown signal are skipped
+            signal(
+                "transaction_action_progress",
+                &(&other, "bar-1.0-1.x86_64", 50u64, 100u64),
+            ),
+            signal("transaction_unknown", &(&session, 1u64)),
+            signal("transaction_script_error", &(&session, "foo-1.0-1.x86_64", 2u32, 1u64)),
+            signal("transaction_action_stop", &(&session, "foo-1.0-1.x86_64", 100u64)),
+            signal("transaction_after_complete", &(&session, true)),
+        ];
+        let events: Vec<TransactionEvent> =
+            decode_transaction_events(futures::stream::iter(messages), SESSION.to_string())
+                .collect()
+                .await;
+        let names: Vec<String> = events
+            .iter()
+            .map(|event| format!("{event:?}").split([' ', '{']).next().unwrap().to_string())
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "BeforeBegin",
+                "ActionStart",
+                "ActionProgress",
+                "ScriptError",
+                "ActionStop",
+                "AfterComplete"
+            ]
+        );
+        assert!(matches!(
+            &events[2],
+            TransactionEvent::ActionProgress { nevra, processed: 50, total: 100 } if nevra == "foo-1.0-1.x86_64"
+        ));
+        assert!(matches!(
+            &events[3],
+            TransactionEvent::ScriptError {
+                scriptlet: ScriptletType::PostInstall,
+                return_code: 1,
+                ..
+            }
+        ));
+    }
+}
+
+// endregion: --- Unit Tests
@@ -4,6 +4,14 @@ use serde::{Deserialize, Serialize};
 use zbus::proxy;
 use zbus::zvariant::{OwnedValue, Type, Value};
 
+// Interface names, used to tell which interface a failed call was made to
+pub(crate) const SESSION_MANAGER: &str = "org.rpm.dnf.v0.SessionManager";
+pub(crate) const BASE: &str = "org.rpm.dnf.v0.Base";
+pub(crate) const REPO: &str = "org.rpm.dnf.v0.rpm.Repo";
+pub(crate) const RPM: &str = "org.rpm.dnf.v0.rpm.Rpm";
+pub(crate) const GOAL: &str = "org.rpm.dnf.v0.Goal";
+pub(crate) const OFFLINE: &str = "org.rpm.dnf.v0.Offline";
+
 #[derive(Debug, Type, Deserialize, Serialize)]
 pub struct ListResults {
     pub items: Vec<HashMap<String, OwnedValue>>,
@@ -0,0 +1,149 @@
+use crate::{Error, Result};
+use std::path::{Path, PathBuf};
+
+/// Repository id of the packages installed from local files and urls
+pub const COMMANDLINE_REPO_ID: &str = "@commandline";
+
+/// Url schemes accepted by dnf5 for remote rpm files
+// -- check dnf5 source code:
+// -- https://github.com/rpm-software-management/dnf5/blob/main/libdnf5/base/goal.cpp
+const URL_SCHEMES: [&str; 4] = ["http", "https", "ftp", "file"];
+
+// region:    --- RpmSource
+
+/// A rpm file to install, given as a local path or as an url.
+/// dnf5daemon-server only treats a spec as file, if it ends with `.rpm`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RpmSource {
+    /// canonical path of a local rpm file (`file://` urls are made local too)
+    File(PathBuf),
+    /// http, https or ftp url of a remote rpm file
+    Url(String),
+}
+
+impl RpmSource {
+    /// Check a local path or url, and make the source to queue.
+    /// Local files must exist and be readable, and the path is canonicalized, so dnf5daemon-server
+    /// (running as root, in another working directory) can find it.
+    pub fn from_spec(spec: &str) -> Result<Self> {
+        if !spec.ends_with(".rpm") {
+            return Err(Error::InvalidArgument(format!("not a rpm file: {spec:?}")));
+        }
+        match spec.split_once("://") {
+            Some((scheme, rest)) => {
+                let scheme = scheme.to_ascii_lowercase();
+                if !URL_SCHEMES.contains(&scheme.as_str()) {
+                    return Err(Error::InvalidArgument(format!("unsupported url scheme: {spec:?}")));
+                }
+                if scheme == "file" {
+                    // only local files, file:///path or file://localhost/path
+                    let path = rest.strip_prefix("localhost").unwrap_or(rest);
+                    if !path.starts_with('/') {
+                        return Err(Error::InvalidArgument(format!("not a local file url: {spec:?}")));
+                    }
+                    return Self::from_path(Path::new(path));
+                }
+                let host = rest.split('/').next().unwrap_or_default();
+                if host.is_empty() || rest.chars().any(char::is_whitespace) {
+                    return Err(Error::InvalidArgument(format!("invalid url: {spec:?}")));
+                }
+                Ok(RpmSource::Url(spec.to_string()))
+            }
+            None => Self::from_path(Path::new(spec)),
+        }
+    }
+
+    /// Check that a local file exist and is readable, and canonicalize the path
+    fn from_path(path: &Path) -> Result<Self> {
+        let invalid = |e: std::io::Error| Error::InvalidArgument(format!("{}: {e}", path.display()));
+        let canonical = path.canonicalize().map_err(invalid)?;
+        if !canonical.is_file() {
+            return Err(Error::InvalidArgument(format!("not a file: {}", path.display())));
+        }
+        std::fs::File::open(&canonical).map_err(invalid)?;
+        Ok(RpmSource::File(canonical))
+    }
+
+    /// Check if the source is a local file
+    pub fn is_local(&self) -> bool {
+        matches!(self, RpmSource::File(_))
+    }
+
+    /// The spec to queue in dnf5daemon-server
+    pub fn to_spec(&self) -> String {
+        match self {
+            RpmSource::File(path) => path.to_string_lossy().to_string(),
+            RpmSource::Url(url) => url.clone(),
+        }
+    }
+}
+
+impl core::fmt::Display for RpmSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_spec())
+    }
+}
+
+// endregion: --- RpmSource
+
+// region:    --- Unit Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rpm_file(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("dnf5daemon-{}-{name}", std::process::id()));
+        std::fs::write(&path, "rpm").unwrap();
+        path
+    }
+
+    #[test]
+    fn local_files() {
+        let path = rpm_file("foo-1.0-1.noarch.rpm");
+        let canonical = path.canonicalize().unwrap();
+        let source = RpmSource::from_spec(path.to_str().unwrap()).unwrap();
+        assert!(source.is_local());
+        assert_eq!(source, RpmSource::File(canonical.clone()));
+        let url = format!("file://{}", path.display());
+        assert_eq!(RpmSource::from_spec(&url).unwrap(), RpmSource::File(canonical.clone()));
+        let url = format!("file://localhost{}", path.display());
+        assert_eq!(RpmSource::from_spec(&url).unwrap(), RpmSource::File(canonical));
+        // a file url with another host is not a local file
+        let url = format!("file://example.com{}", path.display());
+        assert!(RpmSource::from_spec(&url).is_err());
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(
+            RpmSource::from_spec(path.to_str().unwrap()),
+            Err(Error::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn invalid_specs() {
+        let path = rpm_file("foo-1.0-1.noarch.txt");
+        assert!(RpmSource::from_spec(path.to_str().unwrap()).is_err());
+        std::fs::remove_file(&path).unwrap();
+        // a directory is not a rpm file
+        let dir = std::env::temp_dir().join(format!("dnf5daemon-{}-dir.rpm", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        assert!(RpmSource::from_spec(dir.to_str().unwrap()).is_err());
+        std::fs::remove_dir(&dir).unwrap();
+        assert!(RpmSource::from_spec("foo").is_err());
+        assert!(RpmSource::from_spec("scp://host/foo.rpm").is_err());
+        assert!(RpmSource::from_spec("https:///foo.rpm").is_err());
+        assert!(RpmSource::from_spec("file://host/foo.rpm").is_err());
+        assert!(RpmSource::from_spec("https://host/my foo.rpm").is_err());
+    }
+
+    #[test]
+    fn urls() {
+        let url = "https://example.com/pub/foo-1.0-1.noarch.rpm";
+        let source = RpmSource::from_spec(url).unwrap();
+        assert!(!source.is_local());
+        assert_eq!(source.to_spec(), url);
+        assert!(RpmSource::from_spec("FTP://example.com/foo.rpm").is_ok());
+    }
+}
+
+// endregion: --- Unit Tests
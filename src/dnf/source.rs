@@ -0,0 +1,189 @@
+use crate::DnfDaemon;
+use crate::package::{PackageRecord, Scope, get_package_records};
+use crate::{Error, Result};
+use log::warn;
+use std::collections::BTreeMap;
+
+// region:    --- SourceRpm
+
+/// The name, version and release of a source rpm, parsed from the filename
+/// Ex. `openssl-3.0.7-2.fc38.src.rpm` gives name = openssl, version = 3.0.7, release = 2.fc38
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SourceRpm {
+    pub name: String,
+    pub version: String,
+    pub release: String,
+    /// `src` or `nosrc`
+    pub arch: String,
+}
+
+impl SourceRpm {
+    /// Parse a source rpm filename (name-version-release.src.rpm)
+    pub fn parse(filename: &str) -> Result<SourceRpm> {
+        let invalid = || Error::InvalidSourceRpm(filename.to_string());
+        let nvra = filename.strip_suffix(".rpm").ok_or_else(invalid)?;
+        let (nvr, arch) = nvra.rsplit_once('.').ok_or_else(invalid)?;
+        if arch != "src" && arch != "nosrc" {
+            return Err(invalid());
+        }
+        // name can contain '-', so split from the right
+        let mut parts = nvr.rsplitn(3, '-');
+        let release = parts.next().ok_or_else(invalid)?;
+        let version = parts.next().ok_or_else(invalid)?;
+        let name = parts.next().ok_or_else(invalid)?;
+        if name.is_empty() || version.is_empty() || release.is_empty() {
+            return Err(invalid());
+        }
+        Ok(Self {
+            name: name.to_string(),
+            version: version.to_string(),
+            release: release.to_string(),
+            arch: arch.to_string(),
+        })
+    }
+
+    /// name-version-release.arch
+    pub fn nevra(&self) -> String {
+        format!("{}-{}-{}.{}", self.name, self.version, self.release, self.arch)
+    }
+}
+
+impl core::fmt::Display for SourceRpm {
+    /// the source rpm filename
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.rpm", self.nevra())
+    }
+}
+
+// endregion: --- SourceRpm
+
+// region:    --- SourcePackage
+
+/// A source package and the binary packages build from it
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourcePackage {
+    /// name-version-release.src of the source rpm
+    pub src_nevra: String,
+    pub binaries: Vec<PackageRecord>,
+}
+
+impl SourcePackage {
+    /// the parsed source rpm
+    pub fn source_rpm(&self) -> Result<SourceRpm> {
+        SourceRpm::parse(&format!("{}.rpm", self.src_nevra))
+    }
+
+    /// names of the binary packages
+    pub fn binary_names(&self) -> Vec<&str> {
+        self.binaries.iter().map(|pkg| pkg.name.as_str()).collect()
+    }
+}
+
+/// Group package records by the source rpm they are build from.
+/// Packages without a source rpm (Ex. gpg-pubkey) are left out, and packages with a source rpm
+/// that can't be parsed are left out with a warning, so one odd package don't hide the others.
+/// The source packages are sorted by the source rpm nevra.
+pub fn group_by_source(records: Vec<PackageRecord>) -> Vec<SourcePackage> {
+    let mut groups: BTreeMap<String, Vec<PackageRecord>> = BTreeMap::new();
+    for record in records {
+        let Some(sourcerpm) = &record.sourcerpm else {
+            continue;
+        };
+        let src_nevra = match SourceRpm::parse(sourcerpm) {
+            Ok(src) => src.nevra(),
+            Err(e) => {
+                warn!("{} is left out : {e}", record.nevra());
+                continue;
+            }
+        };
+        groups.entry(src_nevra).or_default().push(record);
+    }
+    groups
+        .into_iter()
+        .map(|(src_nevra, binaries)| SourcePackage { src_nevra, binaries })
+        .collect()
+}
+
+/// Get the packages matching the patterns in the given scope (Ex. installed or available),
+/// grouped by the source rpm they are build from.
+pub async fn get_source_packages(
+    daemon: impl AsRef<DnfDaemon>,
+    patterns: impl AsRef<Vec<String>>,
+    scope: Scope,
+) -> Result<Vec<SourcePackage>> {
+    let records = get_package_records(daemon, patterns, scope).await?;
+    Ok(group_by_source(records))
+}
+
+// endregion: --- SourcePackage
+
+// region:    --- Unit Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(name: &str, sourcerpm: Option<&str>) -> PackageRecord {
+        PackageRecord {
+            name: name.to_string(),
+            epoch: "0".to_string(),
+            version: "3.0.7".to_string(),
+            release: "2.fc38".to_string(),
+            arch: "x86_64".to_string(),
+            repo_id: "@System".to_string(),
+            is_installed: true,
+            sourcerpm: sourcerpm.map(|s| s.to_string()),
+        }
+    }
+
+    #[test]
+    fn source_rpm_parse() {
+        let src = SourceRpm::parse("openssl-3.0.7-2.fc38.src.rpm").unwrap();
+        assert_eq!(src.name, "openssl");
+        assert_eq!(src.version, "3.0.7");
+        assert_eq!(src.release, "2.fc38");
+        assert_eq!(src.arch, "src");
+        assert_eq!(src.nevra(), "openssl-3.0.7-2.fc38.src");
+        assert_eq!(src.to_string(), "openssl-3.0.7-2.fc38.src.rpm");
+
+        // name with dashes
+        let src = SourceRpm::parse("python-dnf-plugins-core-4.4.3-1.fc39.nosrc.rpm").unwrap();
+        assert_eq!(src.name, "python-dnf-plugins-core");
+        assert_eq!(src.version, "4.4.3");
+        assert_eq!(src.release, "1.fc39");
+        assert_eq!(src.arch, "nosrc");
+    }
+
+    #[test]
+    fn source_rpm_parse_invalid() {
+        assert!(SourceRpm::parse("").is_err());
+        assert!(SourceRpm::parse("openssl-3.0.7-2.fc38.src").is_err());
+        assert!(SourceRpm::parse("openssl-3.0.7-2.fc38.x86_64.rpm").is_err());
+        assert!(SourceRpm::parse("openssl-3.0.7.src.rpm").is_err());
+        assert!(SourceRpm::parse("-3.0.7-2.src.rpm").is_err());
+        assert!(matches!(
+            SourceRpm::parse("foo.rpm"),
+            Err(Error::InvalidSourceRpm(name)) if name == "foo.rpm"
+        ));
+    }
+
+    #[test]
+    fn group_records_by_source() {
+        let records = vec![
+            record("openssl-libs", Some("openssl-3.0.7-2.fc38.src.rpm")),
+            record("zlib", Some("zlib-1.2.13-3.fc38.src.rpm")),
+            record("openssl", Some("openssl-3.0.7-2.fc38.src.rpm")),
+            record("gpg-pubkey", None),
+            record("odd", Some("odd.rpm")),
+        ];
+        let sources = group_by_source(records);
+        assert_eq!(sources.len(), 2);
+        assert_eq!(sources[0].src_nevra, "openssl-3.0.7-2.fc38.src");
+        assert_eq!(sources[0].binary_names(), vec!["openssl-libs", "openssl"]);
+        assert_eq!(sources[0].source_rpm().unwrap().name, "openssl");
+        assert_eq!(sources[1].src_nevra, "zlib-1.2.13-3.fc38.src");
+        assert_eq!(sources[1].binary_names(), vec!["zlib"]);
+    }
+}
+
+// endregion: --- Unit Tests
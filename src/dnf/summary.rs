@@ -0,0 +1,303 @@
+use crate::dnf::transaction::replacements;
+use crate::package::PackageReason;
+use crate::transaction::{TransactionAction, TransactionMember, TransactionResult};
+
+// region:    --- Helpers
+
+/// Format a size in bytes, using binary units like dnf5 (Ex. `1.5 MiB`)
+pub fn format_size(size: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut value = size as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{size} {}", UNITS[0])
+    } else {
+        format!("{value:.1} {}", UNITS[unit])
+    }
+}
+
+// -- filter selecting the members shown under a heading
+type MemberFilter = fn(&TransactionMember) -> bool;
+
+/// The packages with this action are added to the system
+fn is_inbound(action: &TransactionAction) -> bool {
+    matches!(
+        action,
+        TransactionAction::Install
+            | TransactionAction::Upgrade
+            | TransactionAction::Downgrade
+            | TransactionAction::Reinstall
+    )
+}
+
+/// The packages with this action are removed from the system
+fn is_outbound(action: &TransactionAction) -> bool {
+    matches!(action, TransactionAction::Remove | TransactionAction::Replaced)
+}
+
+// endregion: --- Helpers
+
+// region:    --- TransactionSummary
+
+/// Summary of a resolved transaction, to show before it is confirmed
+///
+/// The `Display` implementation gives a dnf style table, with the members grouped by action
+/// ("Installing:", "Upgrading:", "Removing dependent packages:", ...) and the totals.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransactionSummary {
+    /// number of members for each action, in the order they are first seen
+    pub counts: Vec<(TransactionAction, usize)>,
+    /// size of the packages to download
+    pub download_size: u64,
+    /// installed size of the packages added to the system
+    pub install_size: u64,
+    /// installed size of the packages removed from the system (incl. replaced versions)
+    pub remove_size: u64,
+    /// nevras of the packages installed as dependencies
+    pub dependencies: Vec<String>,
+    /// nevras of the packages installed as weak dependencies
+    pub weak_dependencies: Vec<String>,
+    members: Vec<TransactionMember>,
+}
+
+impl TransactionSummary {
+    /// Make the summary of a resolved transaction
+    pub fn from(result: &TransactionResult) -> Self {
+        let mut summary = Self {
+            counts: Vec::new(),
+            download_size: 0,
+            install_size: 0,
+            remove_size: 0,
+            dependencies: Vec::new(),
+            weak_dependencies: Vec::new(),
+            members: result.tx_members.clone(),
+        };
+        for mbr in &result.tx_members {
+            match summary.counts.iter_mut().find(|(action, _)| action == &mbr.action) {
+                Some((_, count)) => *count += 1,
+                None => summary.counts.push((mbr.action.clone(), 1)),
+            }
+            if is_inbound(&mbr.action) {
+                summary.download_size += mbr.download_size;
+                summary.install_size += mbr.install_size;
+            } else if is_outbound(&mbr.action) {
+                summary.remove_size += mbr.install_size;
+            }
+            if mbr.action == TransactionAction::Install {
+                match mbr.reason {
+                    PackageReason::Dependency => summary.dependencies.push(mbr.nevra.clone()),
+                    PackageReason::WeakDependency => summary.weak_dependencies.push(mbr.nevra.clone()),
+                    _ => {}
+                }
+            }
+        }
+        summary
+    }
+
+    /// The number of members with the given action
+    pub fn count(&self, action: &TransactionAction) -> usize {
+        self.counts
+            .iter()
+            .find(|(a, _)| a == action)
+            .map(|(_, count)| *count)
+            .unwrap_or(0)
+    }
+
+    /// Change of the used disk space, negative if the transaction frees space
+    pub fn install_size_change(&self) -> i64 {
+        self.install_size as i64 - self.remove_size as i64
+    }
+
+    /// The members grouped by the headings used by dnf, empty groups are left out
+    pub fn groups(&self) -> Vec<(&'static str, Vec<&TransactionMember>)> {
+        use TransactionAction as Action;
+        let headings: [(&'static str, MemberFilter); 10] = [
+            ("Installing:", |mbr| {
+                mbr.action == Action::Install
+                    && !matches!(mbr.reason, PackageReason::Dependency | PackageReason::WeakDependency)
+            }),
+            ("Installing dependencies:", |mbr| {
+                mbr.action == Action::Install && mbr.reason == PackageReason::Dependency
+            }),
+            ("Installing weak dependencies:", |mbr| {
+                mbr.action == Action::Install && mbr.reason == PackageReason::WeakDependency
+            }),
+            ("Upgrading:", |mbr| mbr.action == Action::Upgrade),
+            ("Downgrading:", |mbr| mbr.action == Action::Downgrade),
+            ("Reinstalling:", |mbr| mbr.action == Action::Reinstall),
+            ("Removing:", |mbr| {
+                mbr.action == Action::Remove && !matches!(mbr.reason, PackageReason::Dependency | PackageReason::Clean)
+            }),
+            ("Removing dependent packages:", |mbr| {
+                mbr.action == Action::Remove && mbr.reason == PackageReason::Dependency
+            }),
+            ("Removing unused dependencies:", |mbr| {
+                mbr.action == Action::Remove && mbr.reason == PackageReason::Clean
+            }),
+            ("Changing reason:", |mbr| mbr.action == Action::ReasonChange),
+        ];
+        let mut groups: Vec<(&'static str, Vec<&TransactionMember>)> = headings
+            .iter()
+            .map(|(heading, filter)| (*heading, self.members.iter().filter(|mbr| filter(mbr)).collect()))
+            .collect();
+        // the replaced versions are counted in the summary, module and unknown actions are shown last
+        let other: Vec<&TransactionMember> = self
+            .members
+            .iter()
+            .filter(|mbr| {
+                matches!(
+                    mbr.action,
+                    Action::Enable | Action::Disable | Action::Reset | Action::Switch | Action::Unknown(_)
+                )
+            })
+            .collect();
+        groups.push(("Other changes:", other));
+        groups.retain(|(_, members)| !members.is_empty());
+        groups
+    }
+}
+
+impl core::fmt::Display for TransactionSummary {
+    /// dnf style table with the members grouped by action, followed by the totals
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let replacements = replacements(&self.members);
+        for (heading, members) in self.groups() {
+            writeln!(f, "{heading}")?;
+            for mbr in members {
+                writeln!(f, " {:<60} {:>10}", mbr.nevra, format_size(mbr.install_size))?;
+                // the replaced packages are shown below the package that replaces them, like dnf does
+                let replaced = replacements.iter().filter(|r| std::ptr::eq(r.member, mbr));
+                for old in replaced.flat_map(|r| r.replaced.iter()) {
+                    writeln!(
+                        f,
+                        "   replacing {:<50} {:>10}",
+                        old.nevra,
+                        format_size(old.install_size)
+                    )?;
+                }
+            }
+        }
+        writeln!(f)?;
+        writeln!(f, "Transaction Summary:")?;
+        for (action, count) in &self.counts {
+            let packages = if *count == 1 { "package" } else { "packages" };
+            writeln!(f, " {:<20} {count:>4} {packages}", format!("{action}:"))?;
+        }
+        writeln!(f)?;
+        writeln!(
+            f,
+            "Total size of inbound packages is {}.",
+            format_size(self.download_size)
+        )?;
+        let change = self.install_size_change();
+        let (amount, verb) = if change < 0 {
+            (-change, "freed")
+        } else {
+            (change, "used")
+        };
+        write!(
+            f,
+            "After this operation, {} extra will be {verb} (install {}, remove {}).",
+            format_size(amount as u64),
+            format_size(self.install_size),
+            format_size(self.remove_size)
+        )
+    }
+}
+
+// endregion: --- TransactionSummary
+
+// region:    --- Unit Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn member(action: TransactionAction, reason: &str, nevra: &str, size: u64) -> TransactionMember {
+        TransactionMember {
+            download_size: size / 2,
+            install_size: size,
+            ..TransactionMember::new(action, PackageReason::from(reason), nevra)
+        }
+    }
+
+    fn result() -> TransactionResult {
+        TransactionResult {
+            tx_members: vec![
+                member(TransactionAction::Install, "User", "foo-1.0-1.x86_64", 4096),
+                member(TransactionAction::Install, "Dependency", "libfoo-1.0-1.x86_64", 2048),
+                member(
+                    TransactionAction::Install,
+                    "Weak Dependency",
+                    "foo-doc-1.0-1.noarch",
+                    1024,
+                ),
+                member(TransactionAction::Upgrade, "User", "bar-2.0-1.x86_64", 3072),
+                member(TransactionAction::Replaced, "User", "bar-1.0-1.x86_64", 2048),
+                member(TransactionAction::Remove, "Dependency", "baz-1.0-1.x86_64", 1024),
+            ],
+            result_code: 0,
+        }
+    }
+
+    #[test]
+    fn format_sizes() {
+        assert_eq!(format_size(0), "0 B");
+        assert_eq!(format_size(1023), "1023 B");
+        assert_eq!(format_size(1536), "1.5 KiB");
+        assert_eq!(format_size(5 * 1024 * 1024), "5.0 MiB");
+    }
+
+    #[test]
+    fn summary_counts_and_sizes() {
+        let summary = TransactionSummary::from(&result());
+        assert_eq!(summary.count(&TransactionAction::Install), 3);
+        assert_eq!(summary.count(&TransactionAction::Upgrade), 1);
+        assert_eq!(summary.count(&TransactionAction::Downgrade), 0);
+        assert_eq!(summary.counts[0], (TransactionAction::Install, 3));
+        assert_eq!(summary.download_size, 5120);
+        assert_eq!(summary.install_size, 10240);
+        assert_eq!(summary.remove_size, 3072);
+        assert_eq!(summary.install_size_change(), 7168);
+        assert_eq!(summary.dependencies, vec!["libfoo-1.0-1.x86_64"]);
+        assert_eq!(summary.weak_dependencies, vec!["foo-doc-1.0-1.noarch"]);
+    }
+
+    #[test]
+    fn summary_groups_and_table() {
+        let summary = TransactionSummary::from(&result());
+        let headings: Vec<&str> = summary.groups().iter().map(|(heading, _)| *heading).collect();
+        assert_eq!(
+            headings,
+            vec![
+                "Installing:",
+                "Installing dependencies:",
+                "Installing weak dependencies:",
+                "Upgrading:",
+                "Removing dependent packages:",
+            ]
+        );
+        let table = summary.to_string();
+        assert!(table.starts_with("Installing:\n foo-1.0-1.x86_64"));
+        assert!(table.contains("Upgrading:\n bar-2.0-1.x86_64"));
+        assert!(table.contains("\n   replacing bar-1.0-1.x86_64"));
+        assert!(table.contains("Transaction Summary:\n Install:                3 packages\n"));
+        assert!(table.contains(" Remove:                 1 package\n"));
+        assert!(
+            table.ends_with("After this operation, 7.0 KiB extra will be used (install 10.0 KiB, remove 3.0 KiB).")
+        );
+
+        let empty = TransactionSummary::from(&TransactionResult {
+            tx_members: vec![member(TransactionAction::Remove, "User", "foo-1.0-1.x86_64", 2048)],
+            result_code: 0,
+        });
+        assert_eq!(empty.install_size_change(), -2048);
+        assert!(empty.to_string().contains("2.0 KiB extra will be freed"));
+    }
+}
+
+// endregion: --- Unit Tests
@@ -0,0 +1,263 @@
+use crate::DnfDaemon;
+use crate::dnf::options::{DbusOptions, ExecuteOptions, ResolveOptions, as_refs};
+use crate::dnf::package::optional_variant;
+use crate::dnf::proxy::{OFFLINE, RPM};
+use crate::errors::DbusContext;
+use crate::transaction::{Resolved, Transaction};
+use crate::{Error, Result};
+use std::collections::HashMap;
+use zbus::zvariant::{OwnedValue, Value};
+
+// region:    --- Enums
+
+/// How packages are moved to the target release
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UpgradeMode {
+    /// upgrade or downgrade packages to the versions in the target release (default in dnf5)
+    #[default]
+    DistroSync,
+    /// only upgrade packages
+    Upgrade,
+}
+
+impl core::fmt::Display for UpgradeMode {
+    /// String representation, as used by dnf5
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            UpgradeMode::DistroSync => "distrosync",
+            UpgradeMode::Upgrade => "upgrade",
+        };
+        write!(f, "{s}")
+    }
+}
+
+/// What to do when the offline transaction has been run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FinishAction {
+    Reboot,
+    Poweroff,
+}
+
+impl core::fmt::Display for FinishAction {
+    /// String representation, as used by dnf5
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            FinishAction::Reboot => "reboot",
+            FinishAction::Poweroff => "poweroff",
+        };
+        write!(f, "{s}")
+    }
+}
+
+// endregion: --- Enums
+
+// region:    --- OfflineStatus
+
+/// Status of the offline transaction, returned by org.rpm.dnf.v0.Offline.get_status()
+// -- check dnf5 source code:
+// -- https://github.com/rpm-software-management/dnf5/blob/main/dnf5daemon-server/services/offline/offline.cpp
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OfflineStatus {
+    /// an offline transaction is scheduled to run at the next boot
+    pub pending: bool,
+    pub status: Option<String>,
+    pub verb: Option<String>,
+    pub cmd_line: Option<String>,
+    pub system_releasever: Option<String>,
+    pub target_releasever: Option<String>,
+    pub poweroff_after: bool,
+}
+
+impl OfflineStatus {
+    const METHOD: &str = "Offline.get_status()";
+
+    /// Create an OfflineStatus from the reply of Offline.get_status()
+    pub fn from(pending: bool, status: HashMap<String, OwnedValue>) -> Result<Self> {
+        Ok(Self {
+            pending,
+            status: optional_variant(&status, Self::METHOD, "status")?,
+            verb: optional_variant(&status, Self::METHOD, "verb")?,
+            cmd_line: optional_variant(&status, Self::METHOD, "cmd_line")?,
+            system_releasever: optional_variant(&status, Self::METHOD, "system_releasever")?,
+            target_releasever: optional_variant(&status, Self::METHOD, "target_releasever")?,
+            poweroff_after: optional_variant(&status, Self::METHOD, "poweroff_after")?.unwrap_or(false),
+        })
+    }
+}
+
+// endregion: --- OfflineStatus
+
+// region:    --- SystemUpgrade
+
+/// Upgrade the system to a new release (Ex. a new Fedora release), using an offline transaction
+/// that is run at the next boot.
+///
+/// The session must be opened with the target release version.
+///
+/// ``` no_run
+/// # use dnf5daemon::options::SessionOptions;
+/// # use dnf5daemon::system_upgrade::{FinishAction, SystemUpgrade};
+/// # use dnf5daemon::{DnfDaemon, Result};
+/// # async fn upgrade() -> Result<()> {
+/// let dnf_daemon = DnfDaemon::with_options(SessionOptions::new().releasever("41")).await?;
+/// let upgrade = SystemUpgrade::new(&dnf_daemon).allow_erasing(true);
+/// let transaction = upgrade.resolve().await?;
+/// transaction.show();
+/// upgrade.schedule(transaction).await?;
+/// upgrade.set_finish_action(FinishAction::Reboot).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct SystemUpgrade<'a> {
+    dnf_daemon: &'a DnfDaemon,
+    mode: UpgradeMode,
+    allow_erasing: bool,
+}
+
+impl<'a> SystemUpgrade<'a> {
+    /// New SystemUpgrade using distro-sync, without allowing to remove packages
+    pub fn new(dnf_daemon: &'a DnfDaemon) -> Self {
+        Self {
+            dnf_daemon,
+            mode: UpgradeMode::default(),
+            allow_erasing: false,
+        }
+    }
+
+    /// Set how packages are moved to the target release
+    pub fn mode(mut self, mode: UpgradeMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Allow removing installed packages to resolve conflicts (Ex. packages dropped in the new release)
+    pub fn allow_erasing(mut self, allow_erasing: bool) -> Self {
+        self.allow_erasing = allow_erasing;
+        self
+    }
+
+    /// Generate a HashMap with key/value (as variant) pairs to use for Rpm.system_upgrade()
+    fn to_dbus(&self) -> DbusOptions {
+        let mut options = DbusOptions::new();
+        options.insert("mode", Value::new(self.mode.to_string()));
+        options
+    }
+
+    /// Queue the system upgrade and resolve the transaction
+    pub async fn resolve(&self) -> Result<Transaction<'a, Resolved>> {
+        let options = self.to_dbus();
+        self.dnf_daemon
+            .rpm
+            .system_upgrade(as_refs(&options))
+            .await
+            .context(RPM, "system_upgrade")?;
+        Transaction::new(self.dnf_daemon)
+            .with_resolve_options(ResolveOptions::new().allow_erasing(self.allow_erasing))
+            .with_execute_options(ExecuteOptions::new().offline(true))
+            .resolve()
+            .await
+    }
+
+    /// Download the packages of a resolved transaction, and schedule it to run at the next boot
+    pub async fn schedule(&self, transaction: Transaction<'a, Resolved>) -> Result<()> {
+        transaction.execute().await?;
+        let status = self.status().await?;
+        if !status.pending {
+            return Err(Error::DnfDaemon(
+                "the offline transaction was not scheduled".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// The status of the scheduled offline transaction
+    pub async fn status(&self) -> Result<OfflineStatus> {
+        let (pending, status) = self.dnf_daemon.offline.get_status().await.context(OFFLINE, "get_status")?;
+        OfflineStatus::from(pending, status)
+    }
+
+    /// Cancel the scheduled offline transaction
+    pub async fn cancel(&self) -> Result<()> {
+        let (ok, msg) = self.dnf_daemon.offline.cancel().await.context(OFFLINE, "cancel")?;
+        offline_result(ok, msg)
+    }
+
+    /// Set what to do, when the offline transaction has been run
+    pub async fn set_finish_action(&self, action: FinishAction) -> Result<()> {
+        let (ok, msg) = self
+            .dnf_daemon
+            .offline
+            .set_finish_action(&action.to_string())
+            .await
+            .context(OFFLINE, "set_finish_action")?;
+        offline_result(ok, msg)
+    }
+}
+
+/// The Offline methods returns a success flag and an error message
+fn offline_result(ok: bool, msg: String) -> Result<()> {
+    if ok { Ok(()) } else { Err(Error::DnfDaemon(msg)) }
+}
+
+// endregion: --- SystemUpgrade
+
+// region:    --- Unit Tests
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn enums_display() {
+        assert_eq!(UpgradeMode::default().to_string(), "distrosync");
+        assert_eq!(UpgradeMode::Upgrade.to_string(), "upgrade");
+        assert_eq!(FinishAction::Reboot.to_string(), "reboot");
+        assert_eq!(FinishAction::Poweroff.to_string(), "poweroff");
+    }
+
+    #[test]
+    fn offline_status_from() {
+        let mut map = HashMap::new();
+        map.insert(
+            "verb".to_string(),
+            Value::new("system-upgrade").try_into_owned().unwrap(),
+        );
+        map.insert(
+            "target_releasever".to_string(),
+            Value::new("41").try_into_owned().unwrap(),
+        );
+        map.insert("poweroff_after".to_string(), Value::new(true).try_into_owned().unwrap());
+        let status = OfflineStatus::from(true, map).unwrap();
+        assert!(status.pending);
+        assert_eq!(status.verb.as_deref(), Some("system-upgrade"));
+        assert_eq!(status.target_releasever.as_deref(), Some("41"));
+        assert_eq!(status.system_releasever, None);
+        assert!(status.poweroff_after);
+
+        assert_eq!(
+            OfflineStatus::from(false, HashMap::new()).unwrap(),
+            OfflineStatus::default()
+        );
+
+        let mut map = HashMap::new();
+        map.insert(
+            "poweroff_after".to_string(),
+            Value::new("yes").try_into_owned().unwrap(),
+        );
+        assert!(matches!(
+            OfflineStatus::from(true, map),
+            Err(Error::InvalidFieldType { field, .. }) if field == "poweroff_after"
+        ));
+    }
+
+    #[test]
+    fn offline_result_message() {
+        assert!(offline_result(true, String::new()).is_ok());
+        assert!(matches!(
+            offline_result(false, "no offline transaction".to_string()),
+            Err(Error::DnfDaemon(msg)) if msg == "no offline transaction"
+        ));
+    }
+}
+
+// endregion: --- Unit Tests
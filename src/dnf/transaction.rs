@@ -1,20 +1,35 @@
 #[allow(unused)]
 #[allow(dead_code)]
 use crate::DnfDaemon;
+use crate::dnf::evr::Evr;
+use crate::dnf::options::{ActionOptions, ExecuteOptions, ResolveOptions, SessionOptions, as_refs};
+use crate::dnf::package::{ListOptions, PackageAttr, PackageReason, Scope, from_variant, optional_variant};
+use crate::dnf::policy::{TransactionPolicy, check_policies};
+use crate::dnf::problem::ResolveProblem;
+use crate::dnf::progress::{OutcomeRecorder, ScriptError, TransactionEvent, transaction_events};
+use crate::dnf::proxy::{GOAL, GoalProxy, RPM};
+use crate::dnf::rpm_file::{COMMANDLINE_REPO_ID, RpmSource};
+use crate::dnf::summary::TransactionSummary;
+use crate::errors::DbusContext;
 use crate::{Error, Result};
+use futures::Stream;
+use futures_util::StreamExt;
+use log::{debug, warn};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::future::Future;
+use std::task::Poll;
+use std::time::Duration;
 use zbus::zvariant::{OwnedValue, Value};
 
 // region:    --- Types
 
-// -- Custom type for Options Hashmap used in various method calls
-type Options = HashMap<&'static str, &'static Value<'static>>;
-
 // -- Custom type for TransactionPackage
 type TransactionPackage = HashMap<String, OwnedValue>;
 
 // -- Custom type representing one member of the transaction returned by Goal.resolve()
-// -- it contains a typle of (id, action, reason, item attributes, tx_pkg)
+// -- it contains a typle of (object type, action, reason, item attributes, object)
 // -- See https://dnf5.readthedocs.io/en/latest/dnf_daemon/dnf5daemon_dbus_api.8.html#org.rpm.dnf.v0.Goal
 type TransactionItem = (String, String, String, HashMap<String, OwnedValue>, TransactionPackage);
 
@@ -26,8 +41,14 @@ type TransactionItem = (String, String, String, HashMap<String, OwnedValue>, Tra
 // -- check dnf5 source code:
 // -- https://github.com/rpm-software-management/dnf5/blob/3739c4a34db6e7abcd8b4faf0db7d5307f37d340/dnf5daemon-server/transaction.cpp#L30
 // -- https://github.com/rpm-software-management/dnf5/blob/3739c4a34db6e7abcd8b4faf0db7d5307f37d340/dnf5daemon-server/transaction.hpp#L30
-// -- Only a subset of RpmTransactionItemActions is used in dnfdaemon-server.
-#[derive(Debug, PartialEq)]
+// -- https://github.com/rpm-software-management/dnf5/blob/main/include/libdnf5/transaction/transaction_item_action.hpp
+// -- https://github.com/rpm-software-management/dnf5/blob/main/libdnf5/transaction/transaction_item_action.cpp
+///
+/// With the `serde` feature enabled, it (de)serializes as the action string used by dnf5
+/// (`"Install"`, `"Upgrade"`, `"Reason Change"`, ...), `Unknown` keeps the original string.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(from = "String", into = "String"))]
 pub enum TransactionAction {
     Install,
     Upgrade,
@@ -35,6 +56,14 @@ pub enum TransactionAction {
     Reinstall,
     Remove,
     Replaced,
+    /// the reason of an installed package is changed (Ex. a dependency is marked as user installed)
+    ReasonChange,
+    /// a module stream is enabled
+    Enable,
+    Disable,
+    Reset,
+    /// a module is switched to another stream
+    Switch,
     Unknown(String),
 }
 
@@ -48,39 +77,480 @@ impl From<String> for TransactionAction {
             "REINSTALL" => TransactionAction::Reinstall,
             "REMOVE" => TransactionAction::Remove,
             "REPLACED" => TransactionAction::Replaced,
+            "REASON CHANGE" | "REASON_CHANGE" => TransactionAction::ReasonChange,
+            "ENABLE" => TransactionAction::Enable,
+            "DISABLE" => TransactionAction::Disable,
+            "RESET" => TransactionAction::Reset,
+            "SWITCH" => TransactionAction::Switch,
             _ => TransactionAction::Unknown(action),
         }
     }
 }
 
+impl TransactionAction {
+    /// Convert the action number used in the transaction_action_start signal into a TransactionAction enum
+    // -- check dnf5 source code:
+    // -- https://github.com/rpm-software-management/dnf5/blob/main/include/libdnf5/transaction/transaction_item_action.hpp
+    pub fn from_code(code: u32) -> Self {
+        match code {
+            1 => TransactionAction::Install,
+            2 => TransactionAction::Upgrade,
+            3 => TransactionAction::Downgrade,
+            4 => TransactionAction::Reinstall,
+            5 => TransactionAction::Remove,
+            6 => TransactionAction::Replaced,
+            7 => TransactionAction::ReasonChange,
+            8 => TransactionAction::Enable,
+            9 => TransactionAction::Disable,
+            10 => TransactionAction::Reset,
+            11 => TransactionAction::Switch,
+            _ => TransactionAction::Unknown(code.to_string()),
+        }
+    }
+
+    /// The action number used by dnf5, None for `Unknown`
+    pub fn code(&self) -> Option<u32> {
+        let code = match self {
+            TransactionAction::Install => 1,
+            TransactionAction::Upgrade => 2,
+            TransactionAction::Downgrade => 3,
+            TransactionAction::Reinstall => 4,
+            TransactionAction::Remove => 5,
+            TransactionAction::Replaced => 6,
+            TransactionAction::ReasonChange => 7,
+            TransactionAction::Enable => 8,
+            TransactionAction::Disable => 9,
+            TransactionAction::Reset => 10,
+            TransactionAction::Switch => 11,
+            TransactionAction::Unknown(_) => return None,
+        };
+        Some(code)
+    }
+}
+
+impl core::fmt::Display for TransactionAction {
+    /// String representation, as used by dnf5
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            TransactionAction::Install => "Install",
+            TransactionAction::Upgrade => "Upgrade",
+            TransactionAction::Downgrade => "Downgrade",
+            TransactionAction::Reinstall => "Reinstall",
+            TransactionAction::Remove => "Remove",
+            TransactionAction::Replaced => "Replaced",
+            TransactionAction::ReasonChange => "Reason Change",
+            TransactionAction::Enable => "Enable",
+            TransactionAction::Disable => "Disable",
+            TransactionAction::Reset => "Reset",
+            TransactionAction::Switch => "Switch",
+            TransactionAction::Unknown(action) => action,
+        };
+        write!(f, "{s}")
+    }
+}
+
+impl From<TransactionAction> for String {
+    fn from(action: TransactionAction) -> Self {
+        action.to_string()
+    }
+}
+
+/// The reason of a transaction item, it is the same enum as the `reason` package attribute
+/// (User, Dependency, Weak Dependency, Clean, Group, External User)
+pub type TransactionReason = PackageReason;
+
+/// The reason of an installed package is changed by the transaction
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ReasonChange {
+    /// the reason the package is installed with now
+    pub from: TransactionReason,
+    /// the reason after the transaction
+    pub to: TransactionReason,
+}
+
+/// Enum representing the kind of operation queued in a transaction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OperationKind {
+    Install,
+    Remove,
+    Upgrade,
+    Reinstall,
+    Downgrade,
+    DistroSync,
+}
+
+impl core::fmt::Display for OperationKind {
+    /// String representation, the name of the org.rpm.dnf.v0.rpm.Rpm method used to queue it
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            OperationKind::Install => "install",
+            OperationKind::Remove => "remove",
+            OperationKind::Upgrade => "upgrade",
+            OperationKind::Reinstall => "reinstall",
+            OperationKind::Downgrade => "downgrade",
+            OperationKind::DistroSync => "distro_sync",
+        };
+        write!(f, "{s}")
+    }
+}
+
 // endregion: --- Enums
 
+// region:    --- Operation
+/// struct representing an operation queued in a transaction, and the specs it was queued with
+#[derive(Debug, Clone, PartialEq)]
+pub struct Operation {
+    pub kind: OperationKind,
+    pub specs: Vec<String>,
+    pub options: ActionOptions,
+}
+
+impl Operation {
+    /// New Operation of the given kind, using the default options
+    pub fn new(kind: OperationKind, specs: impl AsRef<Vec<String>>) -> Self {
+        Self {
+            kind,
+            specs: specs.as_ref().to_owned(),
+            options: ActionOptions::default(),
+        }
+    }
+
+    /// Set the options used to queue the operation
+    pub fn with_options(mut self, options: ActionOptions) -> Self {
+        self.options = options;
+        self
+    }
+}
+
+// endregion: --- Operation
+
+// region:    --- ResolveReport
+/// struct representing why a transaction could not be resolved.
+/// It contains the problems reported by dnf5daemon-server and the operations queued in the transaction.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolveReport {
+    pub problems: Vec<String>,
+    /// the problems as typed values (empty if they could not be decoded)
+    pub details: Vec<ResolveProblem>,
+    pub operations: Vec<Operation>,
+}
+
+impl ResolveReport {
+    /// The queued specs that caused the problems, with the kind of operation they were queued for.
+    /// The typed problems are used, if they could be decoded, else the argument of the problem messages
+    /// (Ex. `No match for argument: foo`) must match the whole spec.
+    pub fn failed_specs(&self) -> Vec<(OperationKind, &str)> {
+        let failed = |spec: &str| {
+            if self.details.is_empty() {
+                self.problems.iter().any(|problem| problem_argument(problem) == Some(spec))
+            } else {
+                self.details.iter().any(|problem| problem.spec() == Some(spec))
+            }
+        };
+        self.operations
+            .iter()
+            .flat_map(|op| op.specs.iter().map(move |spec| (op.kind, spec.as_str())))
+            .filter(|(_, spec)| failed(spec))
+            .collect()
+    }
+
+    /// The queued operations without the specs that failed, to try again with (Ex. with
+    /// `Transaction::replace_operations`). Operations where all specs failed are left out.
+    /// Only the specs of the typed problems are left out, so no valid spec is dropped when the
+    /// problems could not be decoded.
+    pub fn retry_operations(&self) -> Vec<Operation> {
+        let failed = |spec: &str| self.details.iter().any(|problem| problem.spec() == Some(spec));
+        self.operations
+            .iter()
+            .filter_map(|op| {
+                let specs: Vec<String> = op.specs.iter().filter(|spec| !failed(spec)).cloned().collect();
+                if specs.is_empty() && !op.specs.is_empty() {
+                    return None;
+                }
+                Some(Operation { specs, ..op.clone() })
+            })
+            .collect()
+    }
+}
+
+/// The argument a problem message is about, `No match for argument: foo` or `Argument 'foo' matches ...`
+fn problem_argument(problem: &str) -> Option<&str> {
+    if let Some((_, argument)) = problem.split_once("argument: ") {
+        return Some(argument.trim());
+    }
+    let (_, rest) = problem.split_once("rgument '")?;
+    rest.split_once('\'').map(|(argument, _)| argument)
+}
+
+impl core::fmt::Display for ResolveReport {
+    /// the problems, one per line
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.problems.join("\n"))
+    }
+}
+
+// endregion: --- ResolveReport
+
 // region:    --- TransactionMember
+
+/// The type of object a transaction item is about (the first element of a transaction item)
+// -- check dnf5 source code:
+// -- https://github.com/rpm-software-management/dnf5/blob/main/libdnf5/transaction/transaction_item_type.cpp
+///
+/// With the `serde` feature enabled, it (de)serializes as the type string used by dnf5
+/// (`"Package"`, `"Group"`, ...), `Unknown` keeps the original string.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(from = "String", into = "String"))]
+pub enum ItemType {
+    #[default]
+    Package,
+    Group,
+    Environment,
+    Module,
+    Unknown(String),
+}
+
+impl From<String> for ItemType {
+    /// Convert a string object type into an ItemType enum
+    fn from(item_type: String) -> Self {
+        match item_type.to_uppercase().as_str() {
+            "PACKAGE" => ItemType::Package,
+            "GROUP" => ItemType::Group,
+            "ENVIRONMENT" => ItemType::Environment,
+            "MODULE" => ItemType::Module,
+            _ => ItemType::Unknown(item_type),
+        }
+    }
+}
+
+impl core::fmt::Display for ItemType {
+    /// String representation, as used by dnf5
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            ItemType::Package => "Package",
+            ItemType::Group => "Group",
+            ItemType::Environment => "Environment",
+            ItemType::Module => "Module",
+            ItemType::Unknown(item_type) => item_type,
+        };
+        write!(f, "{s}")
+    }
+}
+
+impl From<ItemType> for String {
+    fn from(item_type: ItemType) -> Self {
+        item_type.to_string()
+    }
+}
+
+/// The id of a transaction item
+///
+/// With the `serde` feature enabled, a package id (de)serializes as a number and other ids as a string.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(untagged))]
+pub enum ItemId {
+    /// the package id, used in the `replaces` attribute of other items
+    Package(i32),
+    /// the id of a group, environment or other non package object
+    Group(String),
+}
+
+impl core::fmt::Display for ItemId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ItemId::Package(id) => write!(f, "{id}"),
+            ItemId::Group(id) => write!(f, "{id}"),
+        }
+    }
+}
+
+/// The transaction item attributes (the fourth element of a transaction item)
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ItemAttributes {
+    /// ids of the packages replaced by this package (Ex. the old version, or obsoleted packages)
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub replaces: Vec<i32>,
+    /// the group, the reason is changed for (only for `Reason Change` actions)
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub reason_change_group_id: Option<String>,
+}
+
+impl ItemAttributes {
+    /// Create the ItemAttributes from the attribute map of a transaction item
+    pub fn from(attrs: &HashMap<String, OwnedValue>) -> Result<Self> {
+        Ok(Self {
+            replaces: optional_variant(attrs, "Goal.resolve()", "replaces")?.unwrap_or_default(),
+            reason_change_group_id: optional_variant(attrs, "Goal.resolve()", "reason_change_group_id")?,
+        })
+    }
+}
+
 /// struct representing a member of a transaction
-#[derive(Debug)]
+///
+/// Most members are packages, but groups and environments are returned by dnf5daemon-server too.
+/// For those `nevra` contains the name of the group or environment, and the package fields are not set.
+///
+/// With the `serde` feature enabled, it (de)serializes as a map with the fields
+/// `action`, `reason`, `nevra`, `reason_change` (`null` when not set), `download_size`, `install_size`,
+/// `item_type`, `id`, `attributes`, `name`, `arch`, `evr`, `repo_id`, `from_repo_id` and `vendor`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct TransactionMember {
     pub action: TransactionAction,
-    pub reason: String,
+    pub reason: TransactionReason,
     pub nevra: String,
-    pub sub_action: Option<String>,
+    /// the package is installed with another reason, than the reason of the transaction item
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub reason_change: Option<ReasonChange>,
+    /// size of the package to download (0 if not known)
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub download_size: u64,
+    /// installed size of the package (0 if not known)
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub install_size: u64,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub item_type: ItemType,
+    /// the package id, or the group/environment id
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub id: Option<ItemId>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub attributes: ItemAttributes,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub name: Option<String>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub arch: Option<String>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub evr: Option<String>,
+    /// the repository the package comes from (`@System` for installed packages)
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub repo_id: Option<String>,
+    /// the repository an installed package was installed from
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub from_repo_id: Option<String>,
+    /// the vendor of the package, it is not returned by Goal.resolve(), so it is looked up with
+    /// Rpm.list() for the members of replacements when the transaction is resolved
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub vendor: Option<String>,
 }
 
 impl TransactionMember {
+    /// New package TransactionMember with the given action, reason and nevra, the other fields are not set
+    pub fn new(action: TransactionAction, reason: TransactionReason, nevra: &str) -> Self {
+        Self {
+            action,
+            reason,
+            nevra: nevra.to_string(),
+            reason_change: None,
+            download_size: 0,
+            install_size: 0,
+            item_type: ItemType::Package,
+            id: None,
+            attributes: ItemAttributes::default(),
+            name: None,
+            arch: None,
+            evr: None,
+            repo_id: None,
+            from_repo_id: None,
+            vendor: None,
+        }
+    }
+
     ///New TransactionMember from action, reason and tx_pkg hashmap return my Goal.resolve method
-    pub fn from(action: String, reason: String, tx_pkg: TransactionPackage) -> Self {
-        let sub_reason = String::try_from(tx_pkg.get("reason").unwrap().to_owned()).unwrap();
-        let full_nevra = String::try_from(tx_pkg.get("full_nevra").unwrap().to_owned()).unwrap();
-        let sub_action = if sub_reason == "None" || sub_reason == *reason {
-            None
-        } else {
-            Some(sub_reason)
+    pub fn from(action: String, reason: String, tx_pkg: TransactionPackage) -> Result<Self> {
+        Self::from_item((ItemType::Package.to_string(), action, reason, HashMap::new(), tx_pkg))
+    }
+
+    /// New TransactionMember from a transaction item returned by Goal.resolve()
+    /// (object type, action, reason, item attributes, object)
+    pub fn from_item(item: TransactionItem) -> Result<Self> {
+        const METHOD: &str = "Goal.resolve()";
+        let (item_type, action, reason, attrs, object) = item;
+        let item_type = ItemType::from(item_type);
+        // objects of an unknown type are decoded as packages, when they have a nevra
+        let is_package = item_type == ItemType::Package
+            || (matches!(item_type, ItemType::Module | ItemType::Unknown(_)) && object.contains_key("full_nevra"));
+        let (nevra, id, sub_reason) = match item_type {
+            _ if is_package => (
+                from_variant!(object, String, "full_nevra", METHOD)?,
+                optional_variant::<i32>(&object, METHOD, "id")?.map(ItemId::Package),
+                Some(from_variant!(object, String, "reason", METHOD)?),
+            ),
+            ItemType::Group => group_name_and_id(&object, "groupid")?,
+            ItemType::Environment => group_name_and_id(&object, "environmentid")?,
+            _ => group_name_and_id(&object, "id")?,
         };
-        Self {
+        let reason = TransactionReason::from(reason);
+        let reason_change = match sub_reason.map(TransactionReason::from) {
+            Some(current) if current != TransactionReason::None && current != reason => Some(ReasonChange {
+                from: current,
+                to: reason.clone(),
+            }),
+            _ => None,
+        };
+        Ok(Self {
             action: action.into(),
             reason,
-            nevra: full_nevra,
-            sub_action,
-        }
+            nevra,
+            reason_change,
+            download_size: optional_variant(&object, METHOD, "download_size")?.unwrap_or(0),
+            install_size: optional_variant(&object, METHOD, "install_size")?.unwrap_or(0),
+            item_type,
+            id,
+            attributes: ItemAttributes::from(&attrs)?,
+            name: optional_variant(&object, METHOD, "name")?,
+            arch: optional_variant(&object, METHOD, "arch")?,
+            evr: optional_variant(&object, METHOD, "evr")?,
+            repo_id: optional_variant(&object, METHOD, "repo_id")?,
+            from_repo_id: optional_variant(&object, METHOD, "from_repo_id")?,
+            vendor: optional_variant(&object, METHOD, "vendor")?,
+        })
+    }
+
+    /// Check if the member is a package installed from a local file or url
+    pub fn is_from_command_line(&self) -> bool {
+        self.repo_id.as_deref() == Some(COMMANDLINE_REPO_ID)
+    }
+
+    /// Check if the member is a package
+    pub fn is_package(&self) -> bool {
+        self.item_type == ItemType::Package
+    }
+
+    /// The name, evr and arch of the package, from the package fields or parsed from the nevra
+    pub fn name_evr_arch(&self) -> (String, Evr, String) {
+        // name-[epoch:]version-release.arch, the name can contain '-', so split from the right
+        let (nvr, arch) = self.nevra.rsplit_once('.').unwrap_or((&self.nevra, ""));
+        let mut parts = nvr.rsplitn(3, '-');
+        let (release, version, name) = (parts.next(), parts.next(), parts.next());
+        let name = match (&self.name, name) {
+            (Some(name), _) => name.to_string(),
+            (None, Some(name)) => name.to_string(),
+            (None, None) => nvr.to_string(),
+        };
+        let evr = match (&self.evr, version, release) {
+            (Some(evr), _, _) => Evr::parse(evr),
+            (None, Some(version), Some(release)) => Evr::parse(&format!("{version}-{release}")),
+            _ => Evr::parse(""),
+        };
+        let arch = self.arch.clone().unwrap_or_else(|| arch.to_string());
+        (name, evr, arch)
+    }
+}
+
+/// The name and id of a group, environment or other non package object, the id is used if it has no name
+fn group_name_and_id(object: &TransactionPackage, id_key: &str) -> Result<(String, Option<ItemId>, Option<String>)> {
+    let id: Option<String> = optional_variant(object, "Goal.resolve()", id_key)?;
+    let name: Option<String> = optional_variant(object, "Goal.resolve()", "name")?;
+    match name.or_else(|| id.clone()) {
+        Some(name) => Ok((name, id.map(ItemId::Group), None)),
+        None => Err(Error::MissingField {
+            field: id_key.to_string(),
+            method: "Goal.resolve()".to_string(),
+        }),
     }
 }
 
@@ -88,7 +558,11 @@ impl TransactionMember {
 
 // region:    --- TransactionResult
 /// struct representing the result of a transaction
-#[derive(Debug)]
+///
+/// With the `serde` feature enabled, it (de)serializes as a map with the fields
+/// `tx_members` (list of [`TransactionMember`]) and `result_code`.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct TransactionResult {
     pub tx_members: Vec<TransactionMember>,
     pub result_code: u32,
@@ -96,13 +570,12 @@ pub struct TransactionResult {
 
 impl TransactionResult {
     /// Create a TransactionResult from a vector of TransactionItems and a result code
-    pub fn from(txmbrs: Vec<(TransactionItem)>, result_code: u32) -> Option<Self> {
+    pub fn from(txmbrs: Vec<(TransactionItem)>, result_code: u32) -> Result<Self> {
         let mut members: Vec<TransactionMember> = Vec::new();
-        for (_, action, reason, _, tx_pkg) in txmbrs {
-            let tx_mbr = TransactionMember::from(action.to_string(), reason.to_string(), tx_pkg.to_owned());
-            members.push(tx_mbr);
+        for item in txmbrs {
+            members.push(TransactionMember::from_item(item)?);
         }
-        Some(Self {
+        Ok(Self {
             tx_members: members,
             result_code,
         })
@@ -113,103 +586,743 @@ impl TransactionResult {
         self.result_code == 0
     }
 
-    /// Show the transaction members
+    /// The transaction members with the given action
+    pub fn members_with_action<'a>(
+        &'a self,
+        action: &'a TransactionAction,
+    ) -> impl Iterator<Item = &'a TransactionMember> {
+        self.tx_members.iter().filter(move |mbr| &mbr.action == action)
+    }
+
+    /// The members installed from local rpm files or urls
+    pub fn command_line_members(&self) -> impl Iterator<Item = &TransactionMember> {
+        self.tx_members.iter().filter(|mbr| mbr.is_from_command_line())
+    }
+
+    /// The members that replace other members (Ex. upgrades and downgrades), linked with the
+    /// `Replaced` members they replace
+    pub fn replacements(&self) -> Vec<Replacement<'_>> {
+        replacements(&self.tx_members)
+    }
+
+    /// Summary with the number of members per action and the total sizes
+    pub fn summary(&self) -> TransactionSummary {
+        TransactionSummary::from(self)
+    }
+
+    /// Show the transaction members grouped by action, and the summary
     pub fn show(&self) {
-        for mbr in &self.tx_members {
-            println!("->> {mbr:?}");
-        }
+        println!("{}", self.summary());
     }
 }
 // endregion: --- TransactionResult
 
+// region:    --- Replacement
+
+/// A transaction member, linked with the `Replaced` members it replaces
+/// An upgrade or downgrade replaces the installed version, and obsoletes can replace packages with
+/// another name (one or more).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Replacement<'a> {
+    pub member: &'a TransactionMember,
+    pub replaced: Vec<&'a TransactionMember>,
+}
+
+impl Replacement<'_> {
+    /// The nevras of the replaced packages
+    pub fn replaced_nevras(&self) -> Vec<&str> {
+        self.replaced.iter().map(|mbr| mbr.nevra.as_str()).collect()
+    }
+
+    /// Packages with another name are replaced (obsoletes)
+    pub fn is_obsoletes(&self) -> bool {
+        let (name, _, _) = self.member.name_evr_arch();
+        self.replaced.iter().any(|mbr| mbr.name_evr_arch().0 != name)
+    }
+
+    /// Only another version of the same package is replaced
+    pub fn is_version_change(&self) -> bool {
+        self.replaced.len() == 1 && !self.is_obsoletes()
+    }
+}
+
+impl core::fmt::Display for Replacement<'_> {
+    /// `foo 1.0-1 → 1.1-1` for a version change, else `foo 1.1-1 (replaces bar-1.0-1.x86_64, ...)`
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (name, evr, _) = self.member.name_evr_arch();
+        if self.is_version_change() {
+            let (_, old_evr, _) = self.replaced[0].name_evr_arch();
+            write!(f, "{name} {old_evr} → {evr}")
+        } else {
+            write!(f, "{name} {evr} (replaces {})", self.replaced_nevras().join(", "))
+        }
+    }
+}
+
+/// Link the members with the `Replaced` members they replace.
+/// The package ids in the `replaces` attribute are used, if dnf5daemon-server returns them, otherwise
+/// upgrades, downgrades and reinstalls are linked to the replaced package with the same name and arch.
+pub(crate) fn replacements(members: &[TransactionMember]) -> Vec<Replacement<'_>> {
+    let replaced: Vec<&TransactionMember> =
+        members.iter().filter(|mbr| mbr.action == TransactionAction::Replaced).collect();
+    let same_package = |a: &TransactionMember, b: &TransactionMember| {
+        let (a_name, _, a_arch) = a.name_evr_arch();
+        let (b_name, _, b_arch) = b.name_evr_arch();
+        a_name == b_name && (a_arch == b_arch || a_arch == "noarch" || b_arch == "noarch")
+    };
+    members
+        .iter()
+        .filter(|mbr| mbr.action != TransactionAction::Replaced)
+        .filter_map(|mbr| {
+            let linked: Vec<&TransactionMember> = if !mbr.attributes.replaces.is_empty() {
+                replaced
+                    .iter()
+                    .filter(|old| matches!(old.id, Some(ItemId::Package(id)) if mbr.attributes.replaces.contains(&id)))
+                    .copied()
+                    .collect()
+            } else if matches!(
+                mbr.action,
+                TransactionAction::Upgrade | TransactionAction::Downgrade | TransactionAction::Reinstall
+            ) {
+                replaced.iter().filter(|old| same_package(mbr, old)).copied().collect()
+            } else {
+                Vec::new()
+            };
+            (!linked.is_empty()).then_some(Replacement {
+                member: mbr,
+                replaced: linked,
+            })
+        })
+        .collect()
+}
+
+// endregion: --- Replacement
+
 // region:    --- Transaction
+
+/// Transaction state: operations can be queued, see [`Transaction`]
+#[derive(Debug)]
+pub struct Pending;
+
+/// Transaction state: the transaction is resolved and can be executed, see [`Transaction`]
+#[derive(Debug)]
+pub struct Resolved {
+    result: TransactionResult,
+}
+
+/// The outcome of an executed transaction, made from the transaction_* signals received while it was running
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransactionOutcome {
+    /// the resolved transaction, that was executed
+    pub result: TransactionResult,
+    /// success flag from the transaction_after_complete signal (None if rpm did not run, Ex. offline transactions)
+    pub success: Option<bool>,
+    /// nevras of the packages that could not be unpacked
+    pub unpack_errors: Vec<String>,
+    pub script_errors: Vec<ScriptError>,
+    pub elapsed: Duration,
+}
+
+impl TransactionOutcome {
+    /// rpm did not report a failure, scriptlet errors are not counted (like in rpm, they don't stop the transaction)
+    pub fn is_success(&self) -> bool {
+        self.success != Some(false) && self.unpack_errors.is_empty()
+    }
+}
+
 /// struct representing a DNF transaction
-pub struct Transaction<'a> {
+///
+/// The state of the transaction is checked at compile time. Packages can only be queued in a
+/// `Transaction<Pending>`, `resolve()` consumes it and gives a `Transaction<Resolved>`, that
+/// contains the plan and can be executed.
+///
+/// ``` no_run
+/// # use dnf5daemon::options::{ActionOptions, ResolveOptions};
+/// # use dnf5daemon::transaction::{OperationKind, Transaction};
+/// # use dnf5daemon::{DnfDaemon, Result};
+/// # async fn upgrade(dnf_daemon: &DnfDaemon) -> Result<()> {
+/// // upgrade from the updates repo, allowing to remove conflicting packages
+/// let mut transaction =
+///     Transaction::new(dnf_daemon).with_resolve_options(ResolveOptions::new().allow_erasing(true));
+/// let options = ActionOptions::new().repo_ids(&["updates"]);
+/// transaction.queue(OperationKind::Upgrade, vec!["dnf5".to_string()], options).await?;
+/// let transaction = transaction.resolve().await?;
+/// transaction.show();
+/// transaction.execute().await?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// Misuse is a compile error, a pending transaction can't be executed
+/// ``` compile_fail
+/// # use dnf5daemon::transaction::Transaction;
+/// # use dnf5daemon::{DnfDaemon, Result};
+/// # async fn install(dnf_daemon: &DnfDaemon) -> Result<()> {
+/// let transaction = Transaction::new(dnf_daemon);
+/// transaction.execute().await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct Transaction<'a, S = Pending> {
     dnf_daemon: &'a DnfDaemon,
-    transaction_result: Option<TransactionResult>,
+    operations: Vec<Operation>,
+    resolve_options: ResolveOptions,
+    execute_options: ExecuteOptions,
+    policies: Vec<Box<dyn TransactionPolicy>>,
+    /// the goal in dnf5daemon-server has packages queued by the transaction, that are not executed or reset
+    goal_queued: bool,
+    state: S,
+}
+
+impl<'a, S> Transaction<'a, S> {
+    /// The operations queued in the transaction
+    pub fn operations(&self) -> &[Operation] {
+        &self.operations
+    }
+
+    /// Move the transaction to another state, with the queued goal
+    fn into_state<T>(mut self, state: T) -> Transaction<'a, T> {
+        let goal_queued = std::mem::replace(&mut self.goal_queued, false);
+        Transaction {
+            dnf_daemon: self.dnf_daemon,
+            operations: std::mem::take(&mut self.operations),
+            resolve_options: std::mem::take(&mut self.resolve_options),
+            execute_options: std::mem::take(&mut self.execute_options),
+            policies: std::mem::take(&mut self.policies),
+            goal_queued,
+            state,
+        }
+    }
+
+    /// Reset the goal in dnf5daemon-server, so nothing is queued
+    async fn reset_goal(&mut self) -> Result<()> {
+        self.dnf_daemon.goal.reset().await.context(GOAL, "reset")?;
+        self.operations.clear();
+        self.goal_queued = false;
+        Ok(())
+    }
+
+    /// Reset the goal after an error, a failed reset is only logged, so the first error is returned
+    async fn reset_after_error(&mut self) {
+        if let Err(e) = self.reset_goal().await {
+            warn!("could not reset the goal after an error : {e}");
+        }
+    }
+}
+
+impl<S> Drop for Transaction<'_, S> {
+    /// Reset the goal, if the transaction is dropped with packages queued in it, else they will be part of
+    /// the next transaction in the session. Blocking on a Dbus call in drop can hang the async runtime, so
+    /// the reset is spawned on the current tokio runtime, and without a runtime it is only logged.
+    fn drop(&mut self) {
+        if self.goal_queued && !spawn_goal_reset(self.dnf_daemon.goal.clone()) {
+            warn!(
+                "transaction dropped without being executed or reset, the goal of session {} still has queued packages",
+                self.dnf_daemon.session_path()
+            );
+        }
+    }
+}
+
+/// A goal with queued packages, that is reset when dropped
+struct QueuedGoal(Option<GoalProxy<'static>>);
+
+impl Drop for QueuedGoal {
+    fn drop(&mut self) {
+        if let Some(goal) = self.0.take()
+            && !spawn_goal_reset(goal)
+        {
+            warn!("transaction stream dropped before it was started, the goal still has queued packages");
+        }
+    }
+}
+
+/// Reset the goal in the background, returns false if there is no tokio runtime to run it on
+fn spawn_goal_reset(goal: GoalProxy<'static>) -> bool {
+    spawn_detached(async move {
+        if let Err(e) = goal.reset().await {
+            warn!("could not reset the goal of a dropped transaction : {e}");
+        }
+    })
+}
+
+/// Spawn a task on the current tokio runtime, returns false if there is no runtime
+fn spawn_detached(task: impl Future<Output = ()> + Send + 'static) -> bool {
+    match tokio::runtime::Handle::try_current() {
+        Ok(handle) => {
+            handle.spawn(task);
+            true
+        }
+        Err(_) => false,
+    }
 }
 
-impl<'a> Transaction<'a> {
+impl<'a> Transaction<'a, Pending> {
     /// Create a new Transaction instance
     pub fn new(dnf_daemon: &'a DnfDaemon) -> Self {
         Self {
             dnf_daemon,
-            transaction_result: None,
+            operations: Vec::new(),
+            resolve_options: ResolveOptions::default(),
+            execute_options: ExecuteOptions::default(),
+            policies: Vec::new(),
+            goal_queued: false,
+            state: Pending,
         }
     }
+
+    /// Set the options used when resolving the transaction
+    pub fn with_resolve_options(mut self, options: ResolveOptions) -> Self {
+        self.resolve_options = options;
+        self
+    }
+
+    /// Set the options used when executing the transaction
+    pub fn with_execute_options(mut self, options: ExecuteOptions) -> Self {
+        self.execute_options = options;
+        self
+    }
+
+    /// Add a policy, that is checked on the resolved transaction before it is executed
+    pub fn with_policy(mut self, policy: impl TransactionPolicy + 'static) -> Self {
+        self.policies.push(Box::new(policy));
+        self
+    }
+
     /// Install packages in the transaction
-    pub async fn install(&self, pkgs: impl AsRef<Vec<String>>) -> Result<()> {
-        let options: Options = HashMap::new();
-        self.dnf_daemon.rpm.install(pkgs.as_ref(), options).await.ok();
-        Ok(())
+    pub async fn install(&mut self, pkgs: impl AsRef<Vec<String>>) -> Result<()> {
+        self.queue(OperationKind::Install, pkgs, ActionOptions::default()).await
+    }
+
+    /// Install local rpm files and remote rpm urls (http, https, ftp or file) in the transaction.
+    /// The files must exist and be readable, and are queued with their canonical path, so
+    /// dnf5daemon-server can find them. The validated sources are returned, use
+    /// [`RpmSource::is_local`] to see which specs are local files.
+    /// Nothing is queued, if one of the specs is not valid.
+    pub async fn install_files(&mut self, paths: &[impl AsRef<str>]) -> Result<Vec<RpmSource>> {
+        let sources = paths
+            .iter()
+            .map(|path| RpmSource::from_spec(path.as_ref()))
+            .collect::<Result<Vec<_>>>()?;
+        let specs: Vec<String> = sources.iter().map(RpmSource::to_spec).collect();
+        self.install(specs).await?;
+        Ok(sources)
     }
 
     /// Remove packages in the transaction
-    pub async fn remove(&self, pkgs: impl AsRef<Vec<String>>) -> Result<()> {
-        let options: Options = HashMap::new();
-        self.dnf_daemon.rpm.remove(pkgs.as_ref(), options).await.ok();
-        Ok(())
+    pub async fn remove(&mut self, pkgs: impl AsRef<Vec<String>>) -> Result<()> {
+        self.queue(OperationKind::Remove, pkgs, ActionOptions::default()).await
     }
 
     /// Update packages in the transaction
-    pub async fn update(&self, pkgs: impl AsRef<Vec<String>>) -> Result<()> {
-        let options: Options = HashMap::new();
-        self.dnf_daemon.rpm.upgrade(pkgs.as_ref(), options).await.ok();
-        Ok(())
+    pub async fn update(&mut self, pkgs: impl AsRef<Vec<String>>) -> Result<()> {
+        self.queue(OperationKind::Upgrade, pkgs, ActionOptions::default()).await
     }
 
     /// Reinstall packages in the transaction
-    pub async fn reinstall(&self, pkgs: impl AsRef<Vec<String>>) -> Result<()> {
-        let options: Options = HashMap::new();
-        self.dnf_daemon.rpm.reinstall(pkgs.as_ref(), options).await.ok();
-        Ok(())
+    pub async fn reinstall(&mut self, pkgs: impl AsRef<Vec<String>>) -> Result<()> {
+        self.queue(OperationKind::Reinstall, pkgs, ActionOptions::default()).await
     }
-    /// Resolve the transaction
-    pub async fn resolve(&mut self) -> Result<()> {
-        let options: Options = HashMap::new();
-
-        if let Ok(rc) = self.dnf_daemon.goal.resolve(options).await {
-            self.transaction_result = TransactionResult::from(rc.0, rc.1);
-            if let Some(result) = &self.transaction_result
-                && !result.is_successful()
-            {
-                let msgs = self.dnf_daemon.goal.get_transaction_problems_string().await;
-                match msgs {
-                    Ok(err_msgs) => {
-                        return Err(Error::TransactionNotResolved(err_msgs.join("\n")));
-                    }
-                    Err(_) => {
-                        return Err(Error::TransactionNotResolved(
-                            "Unknown error during transaction resolution".to_string(),
-                        ));
-                    }
-                }
-            }
+
+    /// Downgrade packages in the transaction to the best version available in the repositories
+    /// An empty list means all installed packages, the ones without a lower version are skipped.
+    pub async fn downgrade(&mut self, pkgs: impl AsRef<Vec<String>>) -> Result<()> {
+        self.queue(OperationKind::Downgrade, pkgs, ActionOptions::default()).await
+    }
+
+    /// Synchronize packages to the versions available in the repositories, upgrading or downgrading them
+    /// as needed. An empty list means all installed packages.
+    pub async fn distro_sync(&mut self, pkgs: impl AsRef<Vec<String>>) -> Result<()> {
+        self.queue(OperationKind::DistroSync, pkgs, ActionOptions::default()).await
+    }
+
+    /// Queue packages in the transaction with the given kind of operation and options
+    pub async fn queue(
+        &mut self,
+        kind: OperationKind,
+        pkgs: impl AsRef<Vec<String>>,
+        options: ActionOptions,
+    ) -> Result<()> {
+        options.validate(kind)?;
+        let pkgs = pkgs.as_ref();
+        // dnf5daemon-server only downgrades the given specs, so the whole system is the installed packages
+        let (specs, dbus_options) = if pkgs.is_empty() && kind == OperationKind::Downgrade {
+            let options = ActionOptions {
+                skip_unavailable: options.skip_unavailable.or(Some(true)),
+                ..options.clone()
+            };
+            (installed_names(self.dnf_daemon).await?, options.to_dbus())
+        } else {
+            (pkgs.clone(), options.to_dbus())
         };
+        let dbus_options = as_refs(&dbus_options);
+        let rpm = &self.dnf_daemon.rpm;
+        // the goal can be changed, also if the call fails
+        self.goal_queued = true;
+        match kind {
+            OperationKind::Install => rpm.install(&specs, dbus_options).await.context(RPM, "install")?,
+            OperationKind::Remove => rpm.remove(&specs, dbus_options).await.context(RPM, "remove")?,
+            OperationKind::Upgrade => rpm.upgrade(&specs, dbus_options).await.context(RPM, "upgrade")?,
+            OperationKind::Reinstall => rpm.reinstall(&specs, dbus_options).await.context(RPM, "reinstall")?,
+            OperationKind::Downgrade => rpm.downgrade(&specs, dbus_options).await.context(RPM, "downgrade")?,
+            OperationKind::DistroSync => rpm.distro_sync(&specs, dbus_options).await.context(RPM, "distro_sync")?,
+        }
+        self.operations.push(Operation::new(kind, pkgs).with_options(options));
         Ok(())
     }
-    /// Execute the transaction
-    pub async fn execute(&mut self) -> Result<()> {
-        let options: Options = HashMap::new();
-        if let Some(result) = &self.transaction_result
-            && result.is_successful()
-        {
-            // everything is Ok, do transaction
-            let _rc = self.dnf_daemon.goal.do_transaction(options.clone()).await.ok();
+
+    /// Clear the queued operations, and reset the goal in dnf5daemon-server
+    pub async fn reset(&mut self) -> Result<()> {
+        self.reset_goal().await
+    }
+
+    /// Replace the queued operations, the goal is reset and the operations are queued again.
+    /// It can be used to edit the operations and resolve again in the same session.
+    ///
+    /// ``` no_run
+    /// # use dnf5daemon::transaction::Transaction;
+    /// # use dnf5daemon::{DnfDaemon, Error, Result};
+    /// # async fn install(dnf_daemon: &DnfDaemon) -> Result<()> {
+    /// let mut transaction = Transaction::new(dnf_daemon);
+    /// transaction.install(vec!["foo".to_string(), "nonexistent".to_string()]).await?;
+    /// let resolved = match transaction.resolve().await {
+    ///     Err(Error::TransactionNotResolved(report)) => {
+    ///         // try again without the specs that failed
+    ///         let mut transaction = Transaction::new(dnf_daemon);
+    ///         transaction.replace_operations(report.retry_operations()).await?;
+    ///         transaction.resolve().await?
+    ///     }
+    ///     rc => rc?,
+    /// };
+    /// resolved.execute().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn replace_operations(&mut self, operations: Vec<Operation>) -> Result<()> {
+        self.reset_goal().await?;
+        for op in operations {
+            self.queue(op.kind, op.specs, op.options).await?;
         }
         Ok(())
     }
 
+    /// Resolve the transaction, and move it to the resolved state
+    /// If it can't be resolved, the problems and the queued operations are returned in a
+    /// [`ResolveReport`] by a `Error::TransactionNotResolved` error, and the goal is reset.
+    pub async fn resolve(mut self) -> Result<Transaction<'a, Resolved>> {
+        match self.resolve_goal().await {
+            Ok(result) => Ok(self.into_state(Resolved { result })),
+            Err(e) => {
+                self.reset_after_error().await;
+                Err(e)
+            }
+        }
+    }
+
+    /// Resolve the goal, a `Error::TransactionNotResolved` error is returned if it can't be resolved
+    async fn resolve_goal(&mut self) -> Result<TransactionResult> {
+        let options = self.resolve_options.to_dbus();
+        // packages can be queued in the goal outside the transaction (Ex. by system_upgrade)
+        self.goal_queued = true;
+
+        let rc = self.dnf_daemon.goal.resolve(as_refs(&options)).await.context(GOAL, "resolve")?;
+        let mut result = TransactionResult::from(rc.0, rc.1)?;
+        if !result.is_successful() {
+            let problems = match self.dnf_daemon.goal.get_transaction_problems_string().await {
+                Ok(err_msgs) => err_msgs,
+                Err(_) => vec!["Unknown error during transaction resolution".to_string()],
+            };
+            let details = match self.problem_details().await {
+                Ok(details) => details,
+                Err(e) => {
+                    warn!("could not decode the transaction problems: {e}");
+                    Vec::new()
+                }
+            };
+            return Err(Error::TransactionNotResolved(ResolveReport {
+                problems,
+                details,
+                operations: self.operations.clone(),
+            }));
+        }
+        fill_vendors(self.dnf_daemon, &mut result).await;
+        Ok(result)
+    }
+
+    /// Resolve the transaction and return what it would change, without executing it.
+    /// The goal is always reset afterwards, also if the transaction can't be resolved, where
+    /// the problems are returned like by [`Transaction::resolve`].
+    ///
+    /// Everything queued in the goal of the session is reset, use [`preview`] to make the
+    /// preview in a new session.
+    pub async fn preview(mut self) -> Result<TransactionResult> {
+        let rc = self.resolve_goal().await;
+        // reset on both paths, a failed reset is returned if the preview itself succeeded
+        match rc {
+            Ok(result) => {
+                self.reset_goal().await?;
+                Ok(result)
+            }
+            Err(e) => {
+                self.reset_after_error().await;
+                Err(e)
+            }
+        }
+    }
+
+    /// The typed problems from org.rpm.dnf.v0.Goal.get_transaction_problems()
+    async fn problem_details(&self) -> Result<Vec<ResolveProblem>> {
+        let problems = self
+            .dnf_daemon
+            .goal
+            .get_transaction_problems()
+            .await
+            .context(GOAL, "get_transaction_problems")?;
+        ResolveProblem::from_problems(&problems)
+    }
+}
+
+impl<'a> Transaction<'a, Resolved> {
+    /// The resolved transaction, with the packages that will be changed
+    pub fn result(&self) -> &TransactionResult {
+        &self.state.result
+    }
+
+    /// Go back to the pending state, to queue more operations and resolve again.
+    /// The queued operations are kept.
+    pub fn edit(self) -> Transaction<'a, Pending> {
+        self.into_state(Pending)
+    }
+
+    /// Go back to the pending state, with the goal reset and no queued operations
+    pub async fn reset(self) -> Result<Transaction<'a, Pending>> {
+        let mut transaction = self.into_state(Pending);
+        transaction.reset_goal().await?;
+        Ok(transaction)
+    }
+
+    /// Check the resolved transaction with the policies, a `Error::PolicyViolation` error is returned
+    /// with all the violations, if one or more policies refuse it
+    pub fn check_policies(&self) -> Result<()> {
+        let violations = check_policies(&self.policies, &self.state.result);
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::PolicyViolation(violations))
+        }
+    }
+
+    /// Execute the transaction, if the policies allow it
+    /// If the policies refuse it, the goal is reset and a `Error::PolicyViolation` error is returned.
+    /// If it fails, a `Error::TransactionFailed` error is returned, with the outcome of the transaction.
+    pub async fn execute(self) -> Result<TransactionOutcome> {
+        let mut events = std::pin::pin!(self.execute_with_progress().await?);
+        while let Some(event) = events.next().await {
+            if let TransactionEvent::Finished(outcome) = event {
+                return outcome;
+            }
+        }
+        Err(Error::DnfDaemon(
+            "the transaction finished without an outcome".to_string(),
+        ))
+    }
+
+    /// Execute the transaction, and follow the progress in a stream of events.
+    /// The transaction is run while the stream is polled, the last event is `TransactionEvent::Finished`
+    /// with the outcome of the transaction.
+    ///
+    /// ``` no_run
+    /// # use dnf5daemon::progress::TransactionEvent;
+    /// # use dnf5daemon::transaction::{Resolved, Transaction};
+    /// # use dnf5daemon::Result;
+    /// # use futures::StreamExt;
+    /// # async fn run(transaction: Transaction<'_, Resolved>) -> Result<()> {
+    /// let mut events = Box::pin(transaction.execute_with_progress().await?);
+    /// while let Some(event) = events.next().await {
+    ///     match event {
+    ///         TransactionEvent::ActionStart { nevra, action, .. } => println!("{action} : {nevra}"),
+    ///         TransactionEvent::Finished(outcome) => {
+    ///             outcome?;
+    ///         }
+    ///         _ => {}
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn execute_with_progress(mut self) -> Result<impl Stream<Item = TransactionEvent> + 'a> {
+        if let Err(e) = self.execute_options.validate().and_then(|_| self.check_policies()) {
+            self.reset_after_error().await;
+            return Err(e);
+        }
+        // subscribe before the transaction is started, so no events are missed
+        let mut events = transaction_events(self.dnf_daemon).await?;
+        // the goal is cleared by executing the transaction, until it is started the stream resets it when dropped
+        self.goal_queued = false;
+        let mut queued = QueuedGoal(Some(self.dnf_daemon.goal.clone()));
+        let execute_options = std::mem::take(&mut self.execute_options);
+        let mut call = Box::pin(do_transaction(self.dnf_daemon, execute_options));
+        let mut recorder = OutcomeRecorder::new();
+        let mut result = Some(std::mem::take(&mut self.state.result));
+        let mut outcome: Option<Result<()>> = None;
+        let mut done = false;
+        Ok(futures::stream::poll_fn(move |cx| {
+            if done {
+                return Poll::Ready(None);
+            }
+            if outcome.is_none() {
+                let polled = call.as_mut().poll(cx);
+                // do_transaction is issued on the first poll
+                queued.0 = None;
+                if let Poll::Ready(rc) = polled {
+                    outcome = Some(rc);
+                }
+            }
+            // signals send before do_transaction returned are already queued, so they are
+            // delivered before the Finished event
+            if let Poll::Ready(Some(event)) = events.poll_next_unpin(cx) {
+                recorder.record(&event);
+                return Poll::Ready(Some(event));
+            }
+            match (outcome.take(), result.take()) {
+                (Some(rc), Some(result)) => {
+                    done = true;
+                    let recorder = std::mem::take(&mut recorder);
+                    Poll::Ready(Some(TransactionEvent::Finished(recorder.finish(result, rc))))
+                }
+                (rc, result_left) => {
+                    outcome = rc;
+                    result = result_left;
+                    Poll::Pending
+                }
+            }
+        }))
+    }
+
     /// Show the transaction result
     pub fn show(&self) {
-        if let Some(result) = &self.transaction_result {
-            result.show();
+        self.state.result.show();
+    }
+}
+
+/// Run the resolved transaction by calling org.rpm.dnf.v0.Goal.do_transaction()
+async fn do_transaction(dnf_daemon: &DnfDaemon, execute_options: ExecuteOptions) -> Result<()> {
+    let options = execute_options.to_dbus();
+    let rc = dnf_daemon
+        .goal
+        .do_transaction(as_refs(&options))
+        .await
+        .context(GOAL, "do_transaction");
+    // the installed packages can have changed (also if it failed), so cached queries are no longer valid
+    dnf_daemon.invalidate_cache();
+    rc
+}
+
+/// The names of the installed packages
+async fn installed_names(dnf_daemon: &DnfDaemon) -> Result<Vec<String>> {
+    let options = ListOptions::builder()
+        .attrs(vec![PackageAttr::Name])
+        .scope(Scope::Installed)
+        .build();
+    let pkgs = dnf_daemon.rpm.list(options.to_dbus()).await.context(RPM, "list")?;
+    let mut names = Vec::new();
+    for pkg in &pkgs.items {
+        names.push(from_variant!(pkg, String, "name")?);
+    }
+    names.sort();
+    names.dedup();
+    Ok(names)
+}
+
+/// Fill in the vendor of the members of replacements (Ex. upgrades and the versions they replace),
+/// as it is not returned by org.rpm.dnf.v0.Goal.resolve(). The vendor is left unknown, if it can't be found.
+async fn fill_vendors(dnf_daemon: &DnfDaemon, result: &mut TransactionResult) {
+    let nevras: Vec<String> = result
+        .replacements()
+        .iter()
+        .flat_map(|replacement| std::iter::once(replacement.member).chain(replacement.replaced.iter().copied()))
+        .filter(|mbr| mbr.vendor.is_none())
+        .map(|mbr| mbr.nevra.clone())
+        .collect();
+    if nevras.is_empty() {
+        return;
+    }
+    let options = ListOptions::builder()
+        .attrs(vec![PackageAttr::FullNevra, PackageAttr::Vendor])
+        .patterns(&nevras)
+        .scope(Scope::All)
+        .build();
+    let pkgs = match dnf_daemon.rpm.list(options.to_dbus()).await.context(RPM, "list") {
+        Ok(pkgs) => pkgs,
+        Err(e) => {
+            warn!("could not get the vendor of the transaction members : {e}");
+            return;
+        }
+    };
+    let mut vendors: HashMap<String, String> = HashMap::new();
+    for pkg in &pkgs.items {
+        if let (Ok(Some(nevra)), Ok(Some(vendor))) = (
+            optional_variant::<String>(pkg, "Rpm.list()", "full_nevra"),
+            optional_variant::<String>(pkg, "Rpm.list()", "vendor"),
+        ) {
+            vendors.insert(nevra, vendor);
         }
     }
+    for mbr in result.tx_members.iter_mut().filter(|mbr| mbr.vendor.is_none()) {
+        mbr.vendor = vendors.get(&mbr.nevra).cloned();
+    }
 }
 
 // endregion: --- Transaction
 
+// region:    --- Preview
+
+/// Show what the operations would change, without touching the system or an existing session.
+/// The operations are resolved in a new session opened with the options, that is closed afterwards.
+/// If the operations can't be resolved, the problems are returned in a `Error::TransactionNotResolved`
+/// error, like by [`Transaction::resolve`].
+///
+/// ``` no_run
+/// # use dnf5daemon::options::{ResolveOptions, SessionOptions};
+/// # use dnf5daemon::transaction::{Operation, OperationKind, TransactionAction, preview};
+/// # use dnf5daemon::Result;
+/// # async fn what_if() -> Result<()> {
+/// let operations = vec![Operation::new(OperationKind::Remove, vec!["glibc-langpack-de".to_string()])];
+/// let result = preview(SessionOptions::default(), ResolveOptions::default(), operations).await?;
+/// for mbr in result.members_with_action(&TransactionAction::Remove) {
+///     println!("would remove {}", mbr.nevra);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub async fn preview(
+    session_options: SessionOptions,
+    resolve_options: ResolveOptions,
+    operations: Vec<Operation>,
+) -> Result<TransactionResult> {
+    let mut dnf_daemon = DnfDaemon::with_options(session_options).await?;
+    let rc = preview_in(&dnf_daemon, resolve_options, operations).await;
+    if let Err(e) = dnf_daemon.close().await {
+        warn!("could not close the preview session : {e}");
+    }
+    rc
+}
+
+/// Queue the operations in the session and preview the transaction
+async fn preview_in(
+    dnf_daemon: &DnfDaemon,
+    resolve_options: ResolveOptions,
+    operations: Vec<Operation>,
+) -> Result<TransactionResult> {
+    let mut transaction = Transaction::new(dnf_daemon).with_resolve_options(resolve_options);
+    for op in operations {
+        transaction.queue(op.kind, op.specs, op.options).await?;
+    }
+    transaction.preview().await
+}
+
+// endregion: --- Preview
+
 // region:    --- Unit Tests
 
 #[cfg(test)]
@@ -259,11 +1372,11 @@ mod tests {
             Value::new("package-1.0-1.x86_64").try_to_owned().unwrap(),
         );
 
-        let member = TransactionMember::from("Install".to_string(), "user".to_string(), tx_pkg);
+        let member = TransactionMember::from("Install".to_string(), "user".to_string(), tx_pkg).unwrap();
         assert_eq!(member.action, TransactionAction::Install);
-        assert_eq!(member.reason, "user");
+        assert_eq!(member.reason, TransactionReason::User);
         assert_eq!(member.nevra, "package-1.0-1.x86_64");
-        assert_eq!(member.sub_action, None);
+        assert_eq!(member.reason_change, None);
 
         // Test with sub_reason
         let mut tx_pkg2 = HashMap::new();
@@ -273,8 +1386,138 @@ mod tests {
             Value::new("dep-2.0-1.x86_64").try_to_owned().unwrap(),
         );
 
-        let member2 = TransactionMember::from("Install".to_string(), "user".to_string(), tx_pkg2);
-        assert_eq!(member2.sub_action, Some("dependency".to_string()));
+        let member2 = TransactionMember::from("Install".to_string(), "user".to_string(), tx_pkg2).unwrap();
+        assert_eq!(
+            member2.reason_change,
+            Some(ReasonChange {
+                from: TransactionReason::Dependency,
+                to: TransactionReason::User,
+            })
+        );
+        assert_eq!(member2.install_size, 0);
+
+        // sizes from tx_pkg
+        let mut tx_pkg3 = HashMap::new();
+        tx_pkg3.insert("reason".to_string(), Value::new("user").try_to_owned().unwrap());
+        tx_pkg3.insert(
+            "full_nevra".to_string(),
+            Value::new("package-1.0-1.x86_64").try_to_owned().unwrap(),
+        );
+        tx_pkg3.insert("download_size".to_string(), Value::new(512u64).try_to_owned().unwrap());
+        tx_pkg3.insert("install_size".to_string(), Value::new(2048u64).try_to_owned().unwrap());
+        let member3 = TransactionMember::from("Install".to_string(), "user".to_string(), tx_pkg3).unwrap();
+        assert_eq!(member3.download_size, 512);
+        assert_eq!(member3.install_size, 2048);
+    }
+
+    #[test]
+    fn transaction_member_from_item() {
+        use std::collections::HashMap;
+        use zbus::zvariant::Value;
+
+        let owned = |v: Value| v.try_to_owned().unwrap();
+        let mut object = HashMap::new();
+        object.insert("id".to_string(), owned(Value::new(42i32)));
+        object.insert("reason".to_string(), owned(Value::new("User")));
+        object.insert("full_nevra".to_string(), owned(Value::new("foo-0:1.1-1.fc41.x86_64")));
+        object.insert("name".to_string(), owned(Value::new("foo")));
+        object.insert("arch".to_string(), owned(Value::new("x86_64")));
+        object.insert("evr".to_string(), owned(Value::new("1.1-1.fc41")));
+        object.insert("repo_id".to_string(), owned(Value::new("updates")));
+        let mut attrs = HashMap::new();
+        attrs.insert("replaces".to_string(), owned(Value::new(vec![7i32, 8i32])));
+        let item = (
+            "Package".to_string(),
+            "Upgrade".to_string(),
+            "User".to_string(),
+            attrs,
+            object,
+        );
+        let member = TransactionMember::from_item(item).unwrap();
+        assert!(member.is_package());
+        assert_eq!(member.action, TransactionAction::Upgrade);
+        assert_eq!(member.id, Some(ItemId::Package(42)));
+        assert_eq!(member.attributes.replaces, vec![7, 8]);
+        assert_eq!(member.name.as_deref(), Some("foo"));
+        assert_eq!(member.evr.as_deref(), Some("1.1-1.fc41"));
+        assert_eq!(member.repo_id.as_deref(), Some("updates"));
+        assert_eq!(member.from_repo_id, None);
+
+        // groups have no nevra and no package reason
+        let mut object = HashMap::new();
+        object.insert("groupid".to_string(), owned(Value::new("development-tools")));
+        object.insert("name".to_string(), owned(Value::new("Development Tools")));
+        let item = (
+            "Group".to_string(),
+            "Install".to_string(),
+            "User".to_string(),
+            HashMap::new(),
+            object,
+        );
+        let member = TransactionMember::from_item(item).unwrap();
+        assert_eq!(member.item_type, ItemType::Group);
+        assert!(!member.is_package());
+        assert_eq!(member.nevra, "Development Tools");
+        assert_eq!(member.id, Some(ItemId::Group("development-tools".to_string())));
+        assert_eq!(member.reason_change, None);
+
+        // an environment without id and name can't be decoded
+        let item = (
+            "Environment".to_string(),
+            "Install".to_string(),
+            "User".to_string(),
+            HashMap::new(),
+            HashMap::new(),
+        );
+        assert!(matches!(
+            TransactionMember::from_item(item),
+            Err(Error::MissingField { field, .. }) if field == "environmentid"
+        ));
+        assert_eq!(ItemType::from("Module".to_string()), ItemType::Module);
+        assert_eq!(ItemType::from("Foo".to_string()).to_string(), "Foo");
+    }
+
+    #[test]
+    fn transaction_member_from_malformed_reply() {
+        use std::collections::HashMap;
+        use zbus::zvariant::Value;
+
+        // full_nevra is missing
+        let mut tx_pkg = HashMap::new();
+        tx_pkg.insert("reason".to_string(), Value::new("User").try_to_owned().unwrap());
+        let result = TransactionMember::from("Install".to_string(), "User".to_string(), tx_pkg);
+        assert!(matches!(
+            result,
+            Err(Error::MissingField { field, method }) if field == "full_nevra" && method == "Goal.resolve()"
+        ));
+
+        // reason has the wrong type
+        let mut tx_pkg = HashMap::new();
+        tx_pkg.insert("reason".to_string(), Value::new(2u32).try_to_owned().unwrap());
+        tx_pkg.insert(
+            "full_nevra".to_string(),
+            Value::new("package-1.0-1.x86_64").try_to_owned().unwrap(),
+        );
+        let result = TransactionMember::from("Install".to_string(), "User".to_string(), tx_pkg);
+        assert!(matches!(
+            result,
+            Err(Error::InvalidFieldType { field, .. }) if field == "reason"
+        ));
+    }
+
+    #[test]
+    fn transaction_result_from_malformed_reply() {
+        use std::collections::HashMap;
+
+        let tx_item = (
+            "1".to_string(),
+            "Install".to_string(),
+            "User".to_string(),
+            HashMap::new(),
+            HashMap::new(),
+        );
+        let result = TransactionResult::from(vec![tx_item], 0);
+        assert!(matches!(result, Err(Error::MissingField { .. })));
     }
 
     #[test]
@@ -302,11 +1545,358 @@ mod tests {
         assert!(result.is_successful());
         assert_eq!(result.result_code, 0);
         assert_eq!(result.tx_members.len(), 1);
+        assert_eq!(result.members_with_action(&TransactionAction::Install).count(), 1);
+        assert_eq!(result.members_with_action(&TransactionAction::Downgrade).count(), 0);
 
         let failed_result = TransactionResult::from(vec![], 1).unwrap();
         assert!(!failed_result.is_successful());
         assert_eq!(failed_result.result_code, 1);
     }
+
+    #[test]
+    fn replacements_link_replaced_members() {
+        use TransactionAction as Action;
+        let member = |action: Action, nevra: &str, id: i32, replaces: Vec<i32>| TransactionMember {
+            id: Some(ItemId::Package(id)),
+            attributes: ItemAttributes {
+                replaces,
+                reason_change_group_id: None,
+            },
+            ..TransactionMember::new(action, TransactionReason::User, nevra)
+        };
+        let result = TransactionResult {
+            tx_members: vec![
+                // version bump, without the replaces attribute
+                TransactionMember::new(Action::Upgrade, TransactionReason::User, "foo-0:1.1-1.x86_64"),
+                TransactionMember::new(Action::Replaced, TransactionReason::User, "foo-0:1.0-1.x86_64"),
+                // obsoletes two packages
+                member(Action::Install, "new-lib-2.0-1.x86_64", 10, vec![11, 12]),
+                member(Action::Replaced, "old-lib-1.0-1.x86_64", 11, vec![]),
+                member(Action::Replaced, "old-lib-devel-1.0-1.x86_64", 12, vec![]),
+                // downgrade, linked by id
+                member(Action::Downgrade, "bar-1:2.0-1.noarch", 20, vec![21]),
+                member(Action::Replaced, "bar-1:2.1-1.noarch", 21, vec![]),
+                TransactionMember::new(Action::Install, TransactionReason::User, "baz-1.0-1.x86_64"),
+            ],
+            result_code: 0,
+        };
+        let replacements = result.replacements();
+        assert_eq!(replacements.len(), 3);
+
+        assert_eq!(replacements[0].member.nevra, "foo-0:1.1-1.x86_64");
+        assert_eq!(replacements[0].replaced_nevras(), vec!["foo-0:1.0-1.x86_64"]);
+        assert!(replacements[0].is_version_change());
+        assert_eq!(replacements[0].to_string(), "foo 1.0-1 → 1.1-1");
+
+        assert_eq!(
+            replacements[1].replaced_nevras(),
+            vec!["old-lib-1.0-1.x86_64", "old-lib-devel-1.0-1.x86_64"]
+        );
+        assert!(replacements[1].is_obsoletes());
+        assert!(!replacements[1].is_version_change());
+        assert_eq!(
+            replacements[1].to_string(),
+            "new-lib 2.0-1 (replaces old-lib-1.0-1.x86_64, old-lib-devel-1.0-1.x86_64)"
+        );
+
+        assert_eq!(replacements[2].to_string(), "bar 1:2.1-1 → 1:2.0-1");
+    }
+
+    #[test]
+    fn member_name_evr_arch() {
+        let member = TransactionMember::new(
+            TransactionAction::Install,
+            TransactionReason::User,
+            "python3-dnf-plugins-core-0:4.4.4-1.fc40.noarch",
+        );
+        let (name, evr, arch) = member.name_evr_arch();
+        assert_eq!(name, "python3-dnf-plugins-core");
+        assert_eq!(evr, Evr::parse("4.4.4-1.fc40"));
+        assert_eq!(arch, "noarch");
+
+        // the package fields are used if they are set
+        let member = TransactionMember {
+            name: Some("foo".to_string()),
+            evr: Some("2.0-1".to_string()),
+            arch: Some("x86_64".to_string()),
+            ..TransactionMember::new(TransactionAction::Install, TransactionReason::User, "ignored")
+        };
+        assert_eq!(
+            member.name_evr_arch(),
+            ("foo".to_string(), Evr::parse("2.0-1"), "x86_64".to_string())
+        );
+    }
+
+    #[test]
+    fn transaction_action_display() {
+        assert_eq!(TransactionAction::Install.to_string(), "Install");
+        assert_eq!(TransactionAction::Replaced.to_string(), "Replaced");
+        assert_eq!(TransactionAction::Unknown("Foo".to_string()).to_string(), "Foo");
+        // Display and From<String> must round-trip
+        let action = TransactionAction::from(TransactionAction::Downgrade.to_string());
+        assert_eq!(action, TransactionAction::Downgrade);
+    }
+
+    #[test]
+    fn resolve_report_failed_specs() {
+        let report = ResolveReport {
+            problems: vec![
+                "Failed to resolve the transaction:".to_string(),
+                "No match for argument: nonexistent-package".to_string(),
+            ],
+            details: Vec::new(),
+            operations: vec![
+                Operation::new(
+                    OperationKind::Install,
+                    vec!["0xFFFF".to_string(), "nonexistent-package".to_string()],
+                ),
+                Operation::new(OperationKind::Remove, vec!["dnf5".to_string()]),
+            ],
+        };
+        assert_eq!(
+            report.failed_specs(),
+            vec![(OperationKind::Install, "nonexistent-package")]
+        );
+        assert_eq!(
+            report.to_string(),
+            "Failed to resolve the transaction:\nNo match for argument: nonexistent-package"
+        );
+        let err = Error::TransactionNotResolved(report);
+        assert!(err.to_string().ends_with("No match for argument: nonexistent-package"));
+
+        // the specs in the typed problems are used too
+        let report = ResolveReport {
+            problems: Vec::new(),
+            details: vec![ResolveProblem::NotInstalled {
+                spec: "dnf5".to_string(),
+                data: crate::problem::ProblemData {
+                    spec: Some("dnf5".to_string()),
+                    ..Default::default()
+                },
+            }],
+            operations: vec![Operation::new(OperationKind::Remove, vec!["dnf5".to_string()])],
+        };
+        assert_eq!(report.failed_specs(), vec![(OperationKind::Remove, "dnf5")]);
+
+        // a spec is not failed, because a longer spec containing it failed
+        let report = ResolveReport {
+            problems: vec![
+                "No match for argument: foo-devel".to_string(),
+                "Argument 'dnf5-plugins' matches only excluded packages.".to_string(),
+            ],
+            details: Vec::new(),
+            operations: vec![Operation::new(
+                OperationKind::Install,
+                vec![
+                    "foo".to_string(),
+                    "foo-devel".to_string(),
+                    "dnf5".to_string(),
+                    "dnf5-plugins".to_string(),
+                ],
+            )],
+        };
+        assert_eq!(
+            report.failed_specs(),
+            vec![(OperationKind::Install, "foo-devel"), (OperationKind::Install, "dnf5-plugins")]
+        );
+    }
+
+    #[test]
+    fn command_line_members() {
+        let member = |nevra: &str, repo_id: &str| TransactionMember {
+            repo_id: Some(repo_id.to_string()),
+            ..TransactionMember::new(TransactionAction::Install, TransactionReason::User, nevra)
+        };
+        let result = TransactionResult {
+            tx_members: vec![
+                member("foo-1.0-1.noarch", "@commandline"),
+                member("libfoo-1.0-1.x86_64", "fedora"),
+            ],
+            result_code: 0,
+        };
+        let nevras: Vec<&str> = result.command_line_members().map(|mbr| mbr.nevra.as_str()).collect();
+        assert_eq!(nevras, vec!["foo-1.0-1.noarch"]);
+        assert!(
+            !TransactionMember::new(TransactionAction::Install, TransactionReason::User, "bar").is_from_command_line()
+        );
+    }
+
+    #[test]
+    fn resolve_report_retry_operations() {
+        let no_match = |spec: &str| ResolveProblem::NoMatch {
+            spec: spec.to_string(),
+            data: crate::problem::ProblemData {
+                spec: Some(spec.to_string()),
+                ..Default::default()
+            },
+        };
+        let report = ResolveReport {
+            problems: vec!["No match for argument: foo-devel".to_string()],
+            details: vec![no_match("foo-devel"), no_match("nonexistent-package")],
+            operations: vec![
+                Operation::new(OperationKind::Install, vec!["foo".to_string(), "foo-devel".to_string()]),
+                Operation::new(OperationKind::Remove, vec!["nonexistent-package".to_string()]),
+                Operation::new(OperationKind::Upgrade, Vec::new()),
+            ],
+        };
+        // operations without specs are kept, operations where all specs failed are left out,
+        // and foo is kept, when only foo-devel failed
+        assert_eq!(
+            report.retry_operations(),
+            vec![
+                Operation::new(OperationKind::Install, vec!["foo".to_string()]),
+                Operation::new(OperationKind::Upgrade, Vec::new()),
+            ]
+        );
+        // nothing is left out without typed problems
+        let report = ResolveReport {
+            details: Vec::new(),
+            ..report
+        };
+        assert_eq!(report.retry_operations(), report.operations);
+    }
+
+    #[test]
+    fn transaction_action_from_code() {
+        assert_eq!(TransactionAction::from_code(1), TransactionAction::Install);
+        assert_eq!(TransactionAction::from_code(3), TransactionAction::Downgrade);
+        assert_eq!(TransactionAction::from_code(6), TransactionAction::Replaced);
+        assert_eq!(TransactionAction::from_code(7), TransactionAction::ReasonChange);
+        assert_eq!(TransactionAction::from_code(11), TransactionAction::Switch);
+        assert_eq!(
+            TransactionAction::from_code(99),
+            TransactionAction::Unknown("99".to_string())
+        );
+        // code, from_code, Display and From<String> must round-trip for all known actions
+        for code in 1..=11 {
+            let action = TransactionAction::from_code(code);
+            assert_eq!(action.code(), Some(code));
+            assert_eq!(TransactionAction::from(action.to_string()), action);
+        }
+        assert_eq!(TransactionAction::Unknown("99".to_string()).code(), None);
+    }
+
+    #[test]
+    fn transaction_reason_round_trip() {
+        use TransactionReason as Reason;
+        for reason in [
+            Reason::None,
+            Reason::Dependency,
+            Reason::User,
+            Reason::Clean,
+            Reason::WeakDependency,
+            Reason::Group,
+            Reason::External,
+        ] {
+            assert_eq!(TransactionReason::from(reason.to_string()), reason);
+        }
+        assert_eq!(TransactionReason::from("External User"), Reason::External);
+        assert_eq!(TransactionReason::from("Foo"), Reason::Unknown("Foo".to_string()));
+    }
+
+    #[test]
+    fn operation_kind_display() {
+        assert_eq!(OperationKind::Install.to_string(), "install");
+        assert_eq!(OperationKind::Remove.to_string(), "remove");
+        assert_eq!(OperationKind::Upgrade.to_string(), "upgrade");
+        assert_eq!(OperationKind::Reinstall.to_string(), "reinstall");
+        assert_eq!(OperationKind::Downgrade.to_string(), "downgrade");
+        assert_eq!(OperationKind::DistroSync.to_string(), "distro_sync");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn transaction_action_serde() {
+        let json = serde_json::to_string(&TransactionAction::Upgrade).unwrap();
+        assert_eq!(json, "\"Upgrade\"");
+        let back: TransactionAction = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, TransactionAction::Upgrade);
+
+        let json = serde_json::to_string(&TransactionAction::ReasonChange).unwrap();
+        assert_eq!(json, "\"Reason Change\"");
+        let back: TransactionAction = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, TransactionAction::ReasonChange);
+
+        let unknown = TransactionAction::Unknown("Foo".to_string());
+        let json = serde_json::to_string(&unknown).unwrap();
+        assert_eq!(json, "\"Foo\"");
+        let back: TransactionAction = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, unknown);
+
+        let json = serde_json::to_string(&TransactionReason::WeakDependency).unwrap();
+        assert_eq!(json, "\"Weak Dependency\"");
+        let back: TransactionReason = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, TransactionReason::WeakDependency);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn transaction_result_serde_round_trip() {
+        let result = TransactionResult {
+            tx_members: vec![
+                TransactionMember {
+                    install_size: 1024,
+                    id: Some(ItemId::Package(42)),
+                    attributes: ItemAttributes {
+                        replaces: vec![7],
+                        reason_change_group_id: None,
+                    },
+                    repo_id: Some("fedora".to_string()),
+                    ..TransactionMember::new(
+                        TransactionAction::Install,
+                        TransactionReason::User,
+                        "package-1.0-1.x86_64",
+                    )
+                },
+                TransactionMember {
+                    reason_change: Some(ReasonChange {
+                        from: TransactionReason::Dependency,
+                        to: TransactionReason::User,
+                    }),
+                    ..TransactionMember::new(TransactionAction::Install, TransactionReason::User, "dep-2.0-1.x86_64")
+                },
+            ],
+            result_code: 0,
+        };
+        let json = serde_json::to_value(&result).unwrap();
+        assert_eq!(
+            json["tx_members"][0],
+            serde_json::json!({
+                "action": "Install",
+                "reason": "User",
+                "nevra": "package-1.0-1.x86_64",
+                "reason_change": null,
+                "download_size": 0,
+                "install_size": 1024,
+                "item_type": "Package",
+                "id": 42,
+                "attributes": { "replaces": [7], "reason_change_group_id": null },
+                "name": null,
+                "arch": null,
+                "evr": null,
+                "repo_id": "fedora",
+                "from_repo_id": null,
+                "vendor": null
+            })
+        );
+        assert_eq!(json["result_code"], 0);
+        let back: TransactionResult = serde_json::from_value(json).unwrap();
+        assert_eq!(back, result);
+    }
+
+    #[test]
+    fn spawn_detached_without_runtime() {
+        assert!(!spawn_detached(async {}));
+    }
+
+    #[tokio::test]
+    async fn spawn_detached_on_runtime() {
+        let (sender, receiver) = futures::channel::oneshot::channel();
+        assert!(spawn_detached(async move {
+            sender.send(42).unwrap();
+        }));
+        assert_eq!(receiver.await.unwrap(), 42);
+    }
 }
 
 // endregion: --- Unit Tests
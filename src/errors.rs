@@ -1,48 +1,415 @@
+use crate::policy::PolicyViolation;
+use crate::transaction::{ResolveReport, TransactionOutcome};
 use derive_more::From;
+use zbus::DBusError;
 
 /// Result type used throughout the dnf5daemon library
 pub type Result<T> = std::result::Result<T, Error>;
 
+// region:    --- DbusCall
+
+/// The interface and method of a call to dnf5daemon-server, used to tell which call that failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DbusCall {
+    pub interface: &'static str,
+    pub method: &'static str,
+}
+
+impl core::fmt::Display for DbusCall {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}.{}", self.interface, self.method)
+    }
+}
+
+// endregion: --- DbusCall
+
+// region:    --- DnfErrorKind
+
+/// The known error names returned by dnf5daemon-server
+// -- check dnf5 source code:
+// -- https://github.com/rpm-software-management/dnf5/blob/main/dnf5daemon-server/dbus.hpp
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DnfErrorKind {
+    /// org.rpm.dnf.v0.Error
+    Generic,
+    /// org.rpm.dnf.v0.rpm.Repo.ConfError
+    RepoConf,
+    /// org.rpm.dnf.v0.rpm.Repo.NoMatchingIdError
+    UnknownRepoId,
+    /// org.rpm.dnf.v0.rpm.Rpm.ResolveError
+    Resolve,
+    /// org.rpm.dnf.v0.rpm.Rpm.TransactionError
+    Transaction,
+    /// Other error name in the org.rpm.dnf.v0 namespace
+    Other(String),
+}
+
+impl From<&str> for DnfErrorKind {
+    /// DnfErrorKind from a D-Bus error name
+    fn from(name: &str) -> Self {
+        match name {
+            "org.rpm.dnf.v0.Error" => DnfErrorKind::Generic,
+            "org.rpm.dnf.v0.rpm.Repo.ConfError" => DnfErrorKind::RepoConf,
+            "org.rpm.dnf.v0.rpm.Repo.NoMatchingIdError" => DnfErrorKind::UnknownRepoId,
+            "org.rpm.dnf.v0.rpm.Rpm.ResolveError" => DnfErrorKind::Resolve,
+            "org.rpm.dnf.v0.rpm.Rpm.TransactionError" => DnfErrorKind::Transaction,
+            _ => DnfErrorKind::Other(name.to_string()),
+        }
+    }
+}
+
+// endregion: --- DnfErrorKind
+
 // region:    --- Error
 /// Enum representing possible errors in dnf5daemon operations
 #[derive(Debug, From)]
 pub enum Error {
     /// Error indicating that a transaction has not been resolved
-    TransactionNotResolved(String),
+    TransactionNotResolved(ResolveReport),
     /// Error indicating an invalid transaction action
     InvalidTransactionAction(String),
-    /// DBus related error
+    /// The resolved transaction was refused by one or more policies, and not executed
+    PolicyViolation(Vec<PolicyViolation>),
+    /// The transaction was executed, but failed. The outcome contains the errors reported while it was running.
+    TransactionFailed {
+        message: String,
+        outcome: Box<TransactionOutcome>,
+        /// the error returned by Goal.do_transaction(), if the call failed
+        source: Option<Box<Error>>,
+    },
+    /// DBus related error, not related to a specific call to dnf5daemon-server
     #[from]
     DBus(zbus::Error),
     /// Error indicating failure to connect to DnfDaemon
     DnfDaemon(String),
+    /// Error indicating a source rpm filename that could not be parsed
+    InvalidSourceRpm(String),
+    /// Error indicating an invalid argument passed to the library
+    InvalidArgument(String),
+    /// IO related error
+    #[from]
+    Io(std::io::Error),
+    /// Error indicating that a field is missing in a reply from dnf5daemon-server
+    MissingField { field: String, method: String },
+    /// Error indicating that a field in a reply from dnf5daemon-server has an unexpected type
+    InvalidFieldType {
+        field: String,
+        method: String,
+        reason: String,
+    },
+    /// dnf5daemon-server is not installed or not running (ServiceUnknown/NameHasNoOwner)
+    ServiceUnavailable {
+        call: DbusCall,
+        message: String,
+        source: Box<zbus::Error>,
+    },
+    /// The caller is not allowed to make the call (AccessDenied/AuthFailed)
+    AccessDenied {
+        call: DbusCall,
+        message: String,
+        source: Box<zbus::Error>,
+    },
+    /// dnf5daemon-server did not reply in time (NoReply/Timeout)
+    NoReply {
+        call: DbusCall,
+        message: String,
+        source: Box<zbus::Error>,
+    },
+    /// dnf5daemon-server returned one of its own errors (org.rpm.dnf.v0.*)
+    Dnf {
+        call: DbusCall,
+        kind: DnfErrorKind,
+        message: String,
+        source: Box<zbus::Error>,
+    },
+    /// The reply from dnf5daemon-server could not be decoded into the expected types
+    VariantDecode { call: DbusCall, source: Box<zbus::Error> },
+    /// Other DBus error returned by a call to dnf5daemon-server
+    Call { call: DbusCall, source: Box<zbus::Error> },
+}
+
+impl Error {
+    /// Map a zbus error returned by a call to dnf5daemon-server into a typed error
+    pub fn from_dbus(source: zbus::Error, call: DbusCall) -> Self {
+        let source = Box::new(source);
+        let (name, message) = match source.as_ref() {
+            zbus::Error::MethodError(name, description, _) => {
+                (name.to_string(), description.clone().unwrap_or_default())
+            }
+            zbus::Error::FDO(fdo) => (
+                fdo.name().to_string(),
+                fdo.description().unwrap_or_default().to_string(),
+            ),
+            zbus::Error::Variant(_) => return Error::VariantDecode { call, source },
+            _ => return Error::Call { call, source },
+        };
+        match name.as_str() {
+            "org.freedesktop.DBus.Error.ServiceUnknown" | "org.freedesktop.DBus.Error.NameHasNoOwner" => {
+                Error::ServiceUnavailable { call, message, source }
+            }
+            "org.freedesktop.DBus.Error.AccessDenied"
+            | "org.freedesktop.DBus.Error.AuthFailed"
+            | "org.freedesktop.DBus.Error.InteractiveAuthorizationRequired" => {
+                Error::AccessDenied { call, message, source }
+            }
+            "org.freedesktop.DBus.Error.NoReply"
+            | "org.freedesktop.DBus.Error.Timeout"
+            | "org.freedesktop.DBus.Error.TimedOut" => Error::NoReply { call, message, source },
+            name if name.starts_with("org.rpm.dnf.v0.") => Error::Dnf {
+                call,
+                kind: DnfErrorKind::from(name),
+                message,
+                source,
+            },
+            _ => Error::Call { call, source },
+        }
+    }
+
+    /// The dnf5daemon-server call that failed, if the error is related to a call
+    pub fn call(&self) -> Option<&DbusCall> {
+        match self {
+            Error::ServiceUnavailable { call, .. }
+            | Error::AccessDenied { call, .. }
+            | Error::NoReply { call, .. }
+            | Error::Dnf { call, .. }
+            | Error::VariantDecode { call, .. }
+            | Error::Call { call, .. } => Some(call),
+            Error::TransactionFailed {
+                source: Some(source), ..
+            } => source.call(),
+            _ => None,
+        }
+    }
 }
 
 impl core::fmt::Display for Error {
     fn fmt(&self, fmt: &mut core::fmt::Formatter) -> core::result::Result<(), core::fmt::Error> {
-        write!(fmt, "{self:?}")
+        match self {
+            Error::TransactionNotResolved(msg) => write!(fmt, "transaction could not be resolved: {msg}"),
+            Error::InvalidTransactionAction(action) => write!(fmt, "invalid transaction action: {action}"),
+            Error::TransactionFailed { message, outcome, .. } => {
+                write!(fmt, "transaction failed: {message}")?;
+                for nevra in &outcome.unpack_errors {
+                    write!(fmt, "; could not unpack {nevra}")?;
+                }
+                for script_error in &outcome.script_errors {
+                    write!(fmt, "; {script_error}")?;
+                }
+                Ok(())
+            }
+            Error::PolicyViolation(violations) => {
+                let violations: Vec<String> = violations.iter().map(|v| v.to_string()).collect();
+                write!(fmt, "transaction refused by policy: {}", violations.join("; "))
+            }
+            Error::DBus(e) => write!(fmt, "D-Bus error: {e}"),
+            Error::DnfDaemon(msg) => write!(fmt, "dnf5daemon error: {msg}"),
+            Error::InvalidSourceRpm(filename) => write!(fmt, "invalid source rpm filename: {filename}"),
+            Error::InvalidArgument(msg) => write!(fmt, "invalid argument: {msg}"),
+            Error::Io(e) => write!(fmt, "IO error: {e}"),
+            Error::MissingField { field, method } => write!(fmt, "field {field:?} is missing in reply from {method}"),
+            Error::InvalidFieldType { field, method, reason } => {
+                write!(
+                    fmt,
+                    "field {field:?} in reply from {method} has an unexpected type: {reason}"
+                )
+            }
+            Error::ServiceUnavailable { call, message, .. } => write!(
+                fmt,
+                "dnf5daemon-server is not installed or not running ({call}): {message}"
+            ),
+            Error::AccessDenied { call, message, .. } => write!(fmt, "not allowed to call {call}: {message}"),
+            Error::NoReply { call, message, .. } => {
+                write!(fmt, "no reply from dnf5daemon-server calling {call}: {message}")
+            }
+            Error::Dnf { call, message, .. } => write!(fmt, "{call} failed: {message}"),
+            Error::VariantDecode { call, source } => write!(fmt, "could not decode the reply from {call}: {source}"),
+            Error::Call { call, source } => write!(fmt, "{call} failed: {source}"),
+        }
     }
 }
 
-impl std::error::Error for Error {}
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::ServiceUnavailable { source, .. }
+            | Error::AccessDenied { source, .. }
+            | Error::NoReply { source, .. }
+            | Error::Dnf { source, .. }
+            | Error::VariantDecode { source, .. }
+            | Error::Call { source, .. } => Some(source.as_ref()),
+            Error::DBus(source) => Some(source),
+            Error::Io(source) => Some(source),
+            Error::TransactionFailed {
+                source: Some(source), ..
+            } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
+}
 // endregion: --- Error
 
+// region:    --- DbusContext
+
+/// Add the interface and method of a dnf5daemon-server call to the errors returned by zbus
+pub(crate) trait DbusContext<T> {
+    fn context(self, interface: &'static str, method: &'static str) -> Result<T>;
+}
+
+impl<T> DbusContext<T> for zbus::Result<T> {
+    fn context(self, interface: &'static str, method: &'static str) -> Result<T> {
+        self.map_err(|e| Error::from_dbus(e, DbusCall { interface, method }))
+    }
+}
+
+// endregion: --- DbusContext
+
 // region:    --- Unit Tests
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::error::Error as _;
+    use zbus::message::Message;
+    use zbus::names::OwnedErrorName;
+
+    const CALL: DbusCall = DbusCall {
+        interface: "org.rpm.dnf.v0.Base",
+        method: "read_all_repos",
+    };
+
+    fn method_error(name: &str, description: &str) -> zbus::Error {
+        let msg = Message::method_call("/org/rpm/dnf/v0", "read_all_repos")
+            .unwrap()
+            .build(&())
+            .unwrap();
+        zbus::Error::MethodError(
+            OwnedErrorName::try_from(name).unwrap(),
+            Some(description.to_string()),
+            msg,
+        )
+    }
 
     #[test]
     fn error_display() {
-        let err = Error::TransactionNotResolved("test message".to_string());
-        assert_eq!(format!("{}", err), "TransactionNotResolved(\"test message\")");
+        let err = Error::TransactionNotResolved(ResolveReport {
+            problems: vec!["test message".to_string()],
+            details: Vec::new(),
+            operations: Vec::new(),
+        });
+        assert_eq!(format!("{}", err), "transaction could not be resolved: test message");
+
+        let err = Error::PolicyViolation(vec![
+            PolicyViolation::new("max-removals", "too many removals", Vec::new()),
+            PolicyViolation::new("allowed-repos", "other repositories", Vec::new()),
+        ]);
+        assert_eq!(
+            err.to_string(),
+            "transaction refused by policy: max-removals: too many removals (); allowed-repos: other repositories ()"
+        );
 
         let err2 = Error::InvalidTransactionAction("invalid action".to_string());
-        assert_eq!(format!("{}", err2), "InvalidTransactionAction(\"invalid action\")");
+        assert_eq!(format!("{}", err2), "invalid transaction action: invalid action");
 
         let err3 = Error::DnfDaemon("connection failed".to_string());
-        assert_eq!(format!("{}", err3), "DnfDaemon(\"connection failed\")");
+        assert_eq!(format!("{}", err3), "dnf5daemon error: connection failed");
+
+        let err4 = Error::MissingField {
+            field: "full_nevra".to_string(),
+            method: "Goal.resolve()".to_string(),
+        };
+        assert_eq!(
+            format!("{}", err4),
+            "field \"full_nevra\" is missing in reply from Goal.resolve()"
+        );
+    }
+
+    #[test]
+    fn service_unavailable() {
+        let fdo = zbus::fdo::Error::ServiceUnknown("The name is not activatable".to_string());
+        let err = Error::from_dbus(zbus::Error::FDO(Box::new(fdo)), CALL);
+        assert!(matches!(err, Error::ServiceUnavailable { .. }));
+        assert_eq!(
+            err.to_string(),
+            "dnf5daemon-server is not installed or not running (org.rpm.dnf.v0.Base.read_all_repos): The name is not activatable"
+        );
+        assert_eq!(err.call(), Some(&CALL));
+        assert!(err.source().is_some());
+
+        let err = Error::from_dbus(
+            method_error("org.freedesktop.DBus.Error.NameHasNoOwner", "no owner"),
+            CALL,
+        );
+        assert!(matches!(err, Error::ServiceUnavailable { message, .. } if message == "no owner"));
+    }
+
+    #[test]
+    fn access_denied_and_no_reply() {
+        let err = Error::from_dbus(method_error("org.freedesktop.DBus.Error.AccessDenied", "denied"), CALL);
+        assert!(matches!(err, Error::AccessDenied { .. }));
+        assert_eq!(
+            err.to_string(),
+            "not allowed to call org.rpm.dnf.v0.Base.read_all_repos: denied"
+        );
+
+        let fdo = zbus::fdo::Error::NoReply("timeout".to_string());
+        let err = Error::from_dbus(zbus::Error::FDO(Box::new(fdo)), CALL);
+        assert!(matches!(err, Error::NoReply { .. }));
+    }
+
+    #[test]
+    fn dnf_errors() {
+        let err = Error::from_dbus(method_error("org.rpm.dnf.v0.Error", "Session not found"), CALL);
+        assert!(matches!(
+            &err,
+            Error::Dnf {
+                kind: DnfErrorKind::Generic,
+                ..
+            }
+        ));
+        assert_eq!(
+            err.to_string(),
+            "org.rpm.dnf.v0.Base.read_all_repos failed: Session not found"
+        );
+        let err = Error::from_dbus(
+            method_error("org.rpm.dnf.v0.rpm.Repo.NoMatchingIdError", "no repo"),
+            CALL,
+        );
+        assert!(matches!(
+            err,
+            Error::Dnf {
+                kind: DnfErrorKind::UnknownRepoId,
+                ..
+            }
+        ));
+        assert_eq!(
+            DnfErrorKind::from("org.rpm.dnf.v0.Foo"),
+            DnfErrorKind::Other("org.rpm.dnf.v0.Foo".to_string())
+        );
+    }
+
+    #[test]
+    fn variant_decode_and_other() {
+        let variant = zbus::zvariant::Error::IncorrectType;
+        let err = Error::from_dbus(zbus::Error::Variant(variant), CALL);
+        assert!(matches!(err, Error::VariantDecode { .. }));
+        assert!(
+            err.to_string()
+                .starts_with("could not decode the reply from org.rpm.dnf.v0.Base.read_all_repos")
+        );
+
+        let err = Error::from_dbus(method_error("org.example.Error", "other"), CALL);
+        assert!(matches!(err, Error::Call { .. }));
+        let err = Error::from_dbus(zbus::Error::InvalidReply, CALL);
+        assert!(matches!(err, Error::Call { .. }));
+    }
+
+    #[test]
+    fn context_on_result() {
+        let result: zbus::Result<()> = Err(zbus::Error::InvalidReply);
+        let err = result.context("org.rpm.dnf.v0.Goal", "resolve").unwrap_err();
+        assert_eq!(err.call().unwrap().to_string(), "org.rpm.dnf.v0.Goal.resolve");
+        let ok: zbus::Result<u32> = Ok(1);
+        assert_eq!(ok.context("org.rpm.dnf.v0.Goal", "resolve").unwrap(), 1);
     }
 }
 
@@ -6,6 +6,20 @@
 //! - Automatic closing of the session when object is `DnfDaemon` instance is droppd
 //! - there is also a get-package method to use the `org.rpm.dnf.V0.rpm.list` method to get packages matching given options.
 //! - There is also a `Transaction` struct to handle transactions via the dnf5daemon Dbus API.
+//! - Typed options for opening sessions and for queueing, resolving and executing transactions.
+//! - Installing local rpm files and remote rpm urls, with validation of the paths and urls.
+//! - Side-effect-free previews of what a transaction would change, in a throwaway session.
+//! - A transaction summary with counts per action, download and installed sizes, and a dnf style table.
+//! - Policies that can refuse a resolved transaction (Ex. protected packages, allowed repositories).
+//! - Typed resolve problems (Ex. no match, broken dependency, protected removal) when a transaction can't be resolved.
+//! - A stream of typed progress events while a transaction is running.
+//! - Monitoring of metadata and package downloads, with aggregated progress and ETA.
+//! - Pluggable handling of repository GPG key import requests.
+//! - A system upgrade workflow, using an offline transaction run at the next boot.
+//! - Query helpers to group binary packages by source rpm.
+//! - Listing of installonly packages (Ex. kernels) and cleanup of old versions.
+//! - Leaf and unneeded package analysis, with an autoremove transaction.
+//! - An opt-in query cache for get_packages, that is invalidated when the session state changes.
 //!
 //! ## Example
 //! A simple example, that
@@ -25,7 +39,7 @@
 //!     match DnfDaemon::default().await {
 //!         Ok(dnf_daemon) => {
 //!             // Ensure repositories are loaded (matches other examples' pattern)
-//!             match dnf_daemon.base.read_all_repos().await {
+//!             match dnf_daemon.read_all_repos().await {
 //!                 Ok(rc) => println!("Read all repos returned: {:?}", rc),
 //!                 Err(e) => eprintln!("Warning: read_all_repos failed: {:?}", e),
 //!             }
@@ -49,8 +63,8 @@
 //!                 }
 //!             }
 //!         }
-//!         Err(_) => {
-//!             eprintln!("Could not connect to dnf5daemon-server");
+//!         Err(e) => {
+//!             eprintln!("Could not connect to dnf5daemon-server: {e}");
 //!         }
 //!     }
 //!
@@ -63,7 +77,20 @@ mod dnf;
 mod errors;
 
 // re-exports
+pub use crate::dnf::autoremove;
+pub use crate::dnf::cache;
 pub use crate::dnf::daemon::DnfDaemon;
+pub use crate::dnf::download;
+pub use crate::dnf::evr;
+pub use crate::dnf::installonly;
+pub use crate::dnf::key_import;
+pub use crate::dnf::options;
 pub use crate::dnf::package;
+pub use crate::dnf::policy;
+pub use crate::dnf::problem;
+pub use crate::dnf::progress;
+pub use crate::dnf::source;
+pub use crate::dnf::summary;
+pub use crate::dnf::system_upgrade;
 pub use crate::dnf::transaction;
-pub use crate::errors::{Error, Result};
+pub use crate::errors::{DbusCall, DnfErrorKind, Error, Result};
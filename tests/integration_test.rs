@@ -8,7 +8,7 @@ async fn daemon_test() {
     if let Ok(mut dnf_daemon) = DnfDaemon::default().await {
         assert!(dnf_daemon.is_connected());
         // Check that we can call a method on one of the interfaces
-        let rc = dnf_daemon.base.read_all_repos().await.ok().unwrap();
+        let rc = dnf_daemon.read_all_repos().await.ok().unwrap();
         assert!(rc);
         // Check that we can get a get some packages, using the high-level API
         let pattern: Vec<String> = vec![String::from("dnf5*")];
@@ -52,13 +52,60 @@ async fn transaction_operations_test() {
         assert!(result.is_ok());
 
         // The resolve method should return an error if the transaction is not resolved
-        let resolve_result = transaction.resolve().await;
-        assert!(matches!(resolve_result, Err(Error::TransactionNotResolved(_))));
-
-        // Test show (should not panic)
-        transaction.show();
+        assert_eq!(transaction.operations().len(), 4);
+        match transaction.resolve().await {
+            Err(Error::TransactionNotResolved(report)) => {
+                assert_eq!(report.operations.len(), 4);
+                assert!(!report.problems.is_empty());
+            }
+            Err(e) => panic!("expected TransactionNotResolved, got {e:?}"),
+            Ok(_) => panic!("expected TransactionNotResolved, got a resolved transaction"),
+        }
         dnf_daemon.close().await.unwrap();
     } else {
         println!("Skipping transaction test: cannot connect to dnf5daemon-server");
     }
 }
+
+#[tokio::test]
+async fn dropped_transaction_test() {
+    if let Ok(mut dnf_daemon) = DnfDaemon::default().await {
+        let mut transaction = Transaction::new(&dnf_daemon);
+        let packages = vec!["nonexistent-package".to_string()];
+        transaction.install(&packages).await.unwrap();
+        // the goal is reset in the background, when a transaction is dropped with queued packages
+        drop(transaction);
+        tokio::task::yield_now().await;
+        let transaction = Transaction::new(&dnf_daemon);
+        if let Err(Error::TransactionNotResolved(report)) = transaction.resolve().await {
+            assert!(
+                !report.problems.iter().any(|problem| problem.contains("nonexistent-package")),
+                "{:?}",
+                report.problems
+            );
+        }
+        dnf_daemon.close().await.unwrap();
+    } else {
+        println!("Skipping dropped transaction test: cannot connect to dnf5daemon-server");
+    }
+}
+
+#[tokio::test]
+async fn query_cache_test() {
+    if let Ok(mut dnf_daemon) = DnfDaemon::default().await {
+        // cache is disabled by default
+        assert!(dnf_daemon.cache_stats().is_none());
+        dnf_daemon.enable_cache();
+        assert!(dnf_daemon.read_all_repos().await.unwrap());
+        let pattern: Vec<String> = vec![String::from("dnf5*")];
+        let first = get_packages(&dnf_daemon, &pattern, Scope::All).await.unwrap();
+        let second = get_packages(&dnf_daemon, &pattern, Scope::All).await.unwrap();
+        assert_eq!(first, second);
+        let stats = dnf_daemon.cache_stats().unwrap();
+        assert_eq!(stats.misses, 1);
+        assert_eq!(stats.hits, 1);
+        dnf_daemon.close().await.unwrap();
+    } else {
+        println!("Skipping query cache test: cannot connect to dnf5daemon-server");
+    }
+}